@@ -1,4 +1,8 @@
 #[macro_use]
 pub(crate) mod const_str;
 pub mod openai_compat;
+#[cfg(test)]
+pub(crate) mod samples;
 pub mod tool;
+#[cfg(feature = "client")]
+pub mod transport;
@@ -0,0 +1,325 @@
+//! Error bodies returned alongside non-2xx statuses
+
+use serde::{Deserialize, Serialize};
+
+/// OpenAI sends string codes like `"invalid_api_key"`, OpenRouter sends the HTTP status as a number
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ErrorCode {
+    Number(u16),
+    Text(String),
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorCode::Number(code) => code.fmt(f),
+            ErrorCode::Text(code) => code.fmt(f),
+        }
+    }
+}
+
+/// The `error` object of a `{"error": {...}}` body, in either OpenAI's or OpenRouter's flavour
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiError {
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub error_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub param: Option<String>,
+    /// OpenRouter only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Box<ApiErrorMetadata>>,
+}
+
+/// What OpenRouter adds about the upstream provider that actually failed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiErrorMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_name: Option<String>,
+    /// The upstream provider's own error, usually its response body as a string
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<serde_json::Value>,
+    /// Why a moderation check rejected the input
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasons: Option<Vec<String>>,
+    /// The part of the input that was flagged by moderation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flagged_input: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_slug: Option<String>,
+}
+
+/// Broad reason a request was rejected, for deciding whether to retry and what to tell the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApiErrorKind {
+    /// Missing, invalid or revoked API key, or a key without access to the model
+    Authentication,
+    /// Too many requests
+    RateLimit,
+    /// Out of credits or quota, sending it again won't help until the account is topped up
+    InsufficientCredits,
+    /// The input plus `max_output_tokens` doesn't fit in the model's context window
+    ContextLengthExceeded,
+    /// Rejected by a moderation or safety filter
+    ContentFilter,
+    /// Anything else wrong with the request itself, sending it again won't help
+    InvalidRequest,
+    /// The provider (or OpenRouter's upstream) is down, overloaded or timed out
+    UpstreamUnavailable,
+    Other,
+}
+
+impl ApiErrorKind {
+    /// Classify from the HTTP status alone, for bodies that aren't an error envelope
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            401 | 403 => ApiErrorKind::Authentication,
+            402 => ApiErrorKind::InsufficientCredits,
+            429 => ApiErrorKind::RateLimit,
+            413 => ApiErrorKind::ContextLengthExceeded,
+            400 | 404 | 409 | 422 => ApiErrorKind::InvalidRequest,
+            408 | 500 | 502..=504 | 520..=529 => ApiErrorKind::UpstreamUnavailable,
+            _ => ApiErrorKind::Other,
+        }
+    }
+
+    /// Whether the same request could succeed later
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            ApiErrorKind::RateLimit | ApiErrorKind::UpstreamUnavailable
+        )
+    }
+}
+
+impl std::fmt::Display for ApiErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiErrorKind::Authentication => "authentication failed",
+            ApiErrorKind::RateLimit => "rate limited",
+            ApiErrorKind::InsufficientCredits => "insufficient credits",
+            ApiErrorKind::ContextLengthExceeded => "context length exceeded",
+            ApiErrorKind::ContentFilter => "blocked by content filter",
+            ApiErrorKind::InvalidRequest => "invalid request",
+            ApiErrorKind::UpstreamUnavailable => "upstream unavailable",
+            ApiErrorKind::Other => "api error",
+        }
+        .fmt(f)
+    }
+}
+
+impl ApiError {
+    /// Parse a `{"error": {...}}` body, `None` if it isn't one
+    pub fn from_body(body: &[u8]) -> Option<Self> {
+        #[derive(Deserialize)]
+        struct Envelope {
+            error: ApiError,
+        }
+
+        serde_json::from_slice::<Envelope>(body)
+            .ok()
+            .map(|envelope| envelope.error)
+    }
+
+    /// Same as [`classify`](Self::classify) without a status, OpenRouter repeats it as the `code` anyway
+    pub fn kind(&self) -> ApiErrorKind {
+        self.classify(None)
+    }
+
+    /// `status` is the HTTP status the body came with.
+    /// The specific signals (OpenAI's string codes and error types, context length messages, moderation metadata) win over the status,
+    /// since e.g. a context length error comes back as a plain 400
+    pub fn classify(&self, status: Option<u16>) -> ApiErrorKind {
+        let code = match &self.code {
+            Some(ErrorCode::Text(code)) => Some(code.as_str()),
+            _ => None,
+        };
+        let status = status.or(match self.code {
+            Some(ErrorCode::Number(code)) => Some(code),
+            _ => None,
+        });
+
+        let message = self.message.to_ascii_lowercase();
+        let context_length = code == Some("context_length_exceeded")
+            || [
+                "context length",
+                "context window",
+                "maximum context",
+                "prompt is too long",
+                "too many tokens",
+            ]
+            .iter()
+            .any(|needle| message.contains(needle));
+        if context_length {
+            return ApiErrorKind::ContextLengthExceeded;
+        }
+
+        let moderated = self
+            .metadata
+            .as_ref()
+            .is_some_and(|metadata| metadata.reasons.is_some() || metadata.flagged_input.is_some());
+        if moderated
+            || matches!(
+                code,
+                Some("content_filter" | "content_policy_violation" | "moderation_blocked")
+            )
+        {
+            return ApiErrorKind::ContentFilter;
+        }
+
+        match code.or(self.error_type.as_deref()) {
+            Some("invalid_api_key" | "invalid_authentication" | "authentication_error") => {
+                return ApiErrorKind::Authentication;
+            }
+            Some("rate_limit_exceeded" | "rate_limit_error") => {
+                return ApiErrorKind::RateLimit;
+            }
+            Some("insufficient_quota") => {
+                return ApiErrorKind::InsufficientCredits;
+            }
+            Some("server_error" | "api_error" | "overloaded_error" | "service_unavailable") => {
+                return ApiErrorKind::UpstreamUnavailable;
+            }
+            _ => {}
+        }
+
+        match status {
+            Some(status) => ApiErrorKind::from_status(status),
+            None if self.error_type.as_deref() == Some("invalid_request_error") => {
+                ApiErrorKind::InvalidRequest
+            }
+            None => ApiErrorKind::Other,
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.code {
+            Some(code) => write!(f, "{}: {}", code, self.message),
+            None => self.message.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_both_envelopes() {
+        let openrouter =
+            ApiError::from_body(br#"{"error":{"code":401,"message":"No auth credentials found"}}"#)
+                .unwrap();
+        assert_eq!(openrouter.code, Some(ErrorCode::Number(401)));
+        assert_eq!(openrouter.to_string(), "401: No auth credentials found");
+
+        let openai = ApiError::from_body(
+            br#"{"error":{"message":"Incorrect API key provided","type":"invalid_request_error","param":null,"code":"invalid_api_key"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            openai.code,
+            Some(ErrorCode::Text("invalid_api_key".to_string()))
+        );
+        assert_eq!(openai.error_type.as_deref(), Some("invalid_request_error"));
+
+        assert!(ApiError::from_body(b"<html>Bad Gateway</html>").is_none());
+    }
+
+    #[test]
+    fn test_openrouter_metadata() {
+        let error = ApiError::from_body(
+            br#"{"error":{"code":502,"message":"Provider returned error","metadata":{"provider_name":"Anthropic","raw":"{\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}"}}}"#,
+        )
+        .unwrap();
+        let metadata = error.metadata.as_ref().unwrap();
+        assert_eq!(metadata.provider_name.as_deref(), Some("Anthropic"));
+        assert!(
+            metadata
+                .raw
+                .as_ref()
+                .unwrap()
+                .as_str()
+                .unwrap()
+                .contains("overloaded_error")
+        );
+        assert_eq!(error.kind(), ApiErrorKind::UpstreamUnavailable);
+        assert!(error.kind().is_retryable());
+    }
+
+    #[test]
+    fn test_classification() {
+        let cases: [(&[u8], Option<u16>, ApiErrorKind); 10] = [
+            (
+                br#"{"error":{"code":401,"message":"No auth credentials found"}}"#,
+                None,
+                ApiErrorKind::Authentication,
+            ),
+            (
+                br#"{"error":{"message":"Incorrect API key provided","type":"invalid_request_error","code":"invalid_api_key"}}"#,
+                Some(401),
+                ApiErrorKind::Authentication,
+            ),
+            (
+                br#"{"error":{"code":429,"message":"Rate limit exceeded: free-models-per-min"}}"#,
+                None,
+                ApiErrorKind::RateLimit,
+            ),
+            (
+                br#"{"error":{"code":402,"message":"Insufficient credits. Add more using https://openrouter.ai/settings/credits"}}"#,
+                None,
+                ApiErrorKind::InsufficientCredits,
+            ),
+            (
+                br#"{"error":{"message":"You exceeded your current quota, please check your plan and billing details.","type":"insufficient_quota","param":null,"code":"insufficient_quota"}}"#,
+                Some(429),
+                ApiErrorKind::InsufficientCredits,
+            ),
+            (
+                br#"{"error":{"message":"This model's maximum context length is 128000 tokens.","type":"invalid_request_error","param":"input","code":"context_length_exceeded"}}"#,
+                Some(400),
+                ApiErrorKind::ContextLengthExceeded,
+            ),
+            (
+                br#"{"error":{"code":400,"message":"prompt is too long: 210000 tokens > 200000 maximum"}}"#,
+                None,
+                ApiErrorKind::ContextLengthExceeded,
+            ),
+            (
+                br#"{"error":{"code":403,"message":"Input was flagged","metadata":{"reasons":["violence"],"flagged_input":"...","provider_name":"OpenAI","model_slug":"openai/gpt-5.2"}}}"#,
+                None,
+                ApiErrorKind::ContentFilter,
+            ),
+            (
+                br#"{"error":{"message":"Unsupported parameter: 'temperature'","type":"invalid_request_error","param":"temperature","code":null}}"#,
+                None,
+                ApiErrorKind::InvalidRequest,
+            ),
+            (
+                br#"{"error":{"message":"The server had an error while processing your request.","type":"server_error"}}"#,
+                Some(500),
+                ApiErrorKind::UpstreamUnavailable,
+            ),
+        ];
+
+        for (body, status, expected) in cases {
+            let error = ApiError::from_body(body).unwrap();
+            assert_eq!(error.classify(status), expected, "{}", error.message);
+            assert_eq!(
+                error.classify(status).is_retryable(),
+                matches!(
+                    expected,
+                    ApiErrorKind::RateLimit | ApiErrorKind::UpstreamUnavailable
+                ),
+                "{}",
+                error.message
+            );
+        }
+    }
+}
@@ -0,0 +1,13 @@
+//! Module for the OAI Compatible chat completions endpoint, for the servers (vLLM, llama.cpp, Ollama, LM Studio) that don't speak responses
+
+use std::sync::LazyLock;
+
+use url::Url;
+
+pub const OPENROUTER_CHAT_COMPLETIONS_ENDPOINT: &str =
+    "https://openrouter.ai/api/v1/chat/completions";
+pub static OPENROUTER_CHAT_COMPLETIONS_URL: LazyLock<Url> =
+    LazyLock::new(|| OPENROUTER_CHAT_COMPLETIONS_ENDPOINT.parse().unwrap());
+
+pub mod request;
+pub mod stream;
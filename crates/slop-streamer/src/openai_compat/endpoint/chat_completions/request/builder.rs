@@ -0,0 +1,146 @@
+//! Builder for [`Request`], mirroring the responses one
+
+use crate::openai_compat::endpoint::{
+    chat_completions::request::{Request, RequestOptions, StreamOptions},
+    responses::request::{
+        openrouter::{OpenRouterOptions, ProviderPreferences},
+        tools::ToolCollection,
+    },
+};
+
+/// Builder for [`Request`]
+///
+/// Defaults to `stream: true` with `stream_options.include_usage` so the last chunk carries usage, everything else is left unset and skipped when serializing
+#[derive(Debug, Clone)]
+pub struct RequestBuilder<I, M, T = ()> {
+    request: Request<I, M, T>,
+}
+
+macro_rules! builder_setters {
+    ($($field:ident: $ty:ty),* $(,)?) => {
+        $(
+            pub fn $field(mut self, $field: $ty) -> Self {
+                self.request.options.$field = Some($field);
+                self
+            }
+        )*
+    };
+    (into $($field:ident: $ty:ty),* $(,)?) => {
+        $(
+            pub fn $field(mut self, $field: impl Into<$ty>) -> Self {
+                self.request.options.$field = Some($field.into());
+                self
+            }
+        )*
+    };
+}
+
+impl<I, M> RequestBuilder<I, M> {
+    pub fn new(messages: I, model: M) -> Self {
+        Self {
+            request: Request {
+                messages,
+                model,
+                stream: true,
+                stream_options: Some(StreamOptions {
+                    include_usage: true,
+                }),
+                tools: (),
+                options: RequestOptions::default(),
+            },
+        }
+    }
+}
+
+impl<I, M, T> RequestBuilder<I, M, T> {
+    /// Turning streaming off also drops `stream_options`, which servers reject on non-streaming requests
+    pub fn stream(mut self, stream: bool) -> Self {
+        self.request.stream = stream;
+        if !stream {
+            self.request.stream_options = None;
+        }
+        self
+    }
+
+    pub fn stream_options(mut self, stream_options: StreamOptions) -> Self {
+        self.request.stream_options = Some(stream_options);
+        self
+    }
+
+    builder_setters!(
+        parallel_tool_calls: bool,
+        temperature: f64,
+        top_p: f64,
+        max_completion_tokens: u64,
+        max_tokens: u64,
+        presence_penalty: f64,
+        frequency_penalty: f64,
+        seed: u64,
+    );
+
+    builder_setters!(into
+        user: String,
+    );
+
+    /// Up to 4 sequences where the model stops generating, replacing any previously set
+    pub fn stop<S>(mut self, stop: impl IntoIterator<Item = S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.request.options.stop = Some(stop.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// OpenRouter only, models to fall back to in order, replacing any previously set
+    pub fn fallback_models<S>(mut self, models: impl IntoIterator<Item = S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.request
+            .options
+            .openrouter
+            .get_or_insert_with(OpenRouterOptions::default)
+            .models = models.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// OpenRouter only, how to pick among the providers serving the model
+    pub fn provider(mut self, provider: ProviderPreferences) -> Self {
+        self.request
+            .options
+            .openrouter
+            .get_or_insert_with(OpenRouterOptions::default)
+            .provider = Some(provider);
+        self
+    }
+
+    /// Set the tools the model may call, replacing any previously set
+    pub fn tools<U>(self, tools: U) -> RequestBuilder<I, M, U>
+    where
+        U: ToolCollection,
+    {
+        let Request {
+            messages,
+            model,
+            stream,
+            stream_options,
+            tools: _,
+            options,
+        } = self.request;
+
+        RequestBuilder {
+            request: Request {
+                messages,
+                model,
+                stream,
+                stream_options,
+                tools,
+                options,
+            },
+        }
+    }
+
+    pub fn build(self) -> Request<I, M, T> {
+        self.request
+    }
+}
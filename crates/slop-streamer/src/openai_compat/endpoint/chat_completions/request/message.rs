@@ -0,0 +1,293 @@
+//! The `messages` array, built the same way as the responses input items: every message role is a contract you implement on your own types.
+//! Chat completions only has a handful of roles so this is a lot smaller, content is plain text since that's all the local servers reliably accept.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeSeq};
+use slop_streamer_proc_macros::contract_trait;
+
+mod private {
+    pub trait Sealed {}
+}
+
+pub trait ChatMessage: private::Sealed + erased_serde::Serialize {
+    fn as_erased(&self) -> &dyn erased_serde::Serialize
+    where
+        Self: Sized,
+    {
+        self
+    }
+}
+
+// Same as AsInputItem, lets user enums of message types be used without implementing the sealed trait themselves
+pub trait AsChatMessage {
+    fn erase_variant(&self) -> &dyn ChatMessage;
+}
+erased_serde::serialize_trait_object!(ChatMessage);
+
+impl<T> AsChatMessage for &T
+where
+    T: AsChatMessage + ?Sized,
+{
+    fn erase_variant(&self) -> &dyn ChatMessage {
+        <T as AsChatMessage>::erase_variant(self)
+    }
+}
+
+pub trait ChatMessageCollection {
+    fn serialize_messages<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+
+    /// Get the number of messages (if known)
+    fn size_hint(&self) -> Option<usize> {
+        None
+    }
+}
+
+macro_rules! impl_chat_message_collection {
+    ($($ty:ty),*) => {
+        $(
+            impl<T> ChatMessageCollection for $ty
+            where
+                T: AsChatMessage,
+            {
+                fn serialize_messages<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    let mut seq = serializer.serialize_seq(self.size_hint())?;
+                    for message in self.iter() {
+                        seq.serialize_element(message.erase_variant())?;
+                    }
+                    seq.end()
+                }
+
+                fn size_hint(&self) -> Option<usize> {
+                    Some(self.len())
+                }
+            }
+        )*
+    };
+}
+
+impl_chat_message_collection!(
+    Vec<T>,
+    &[T],
+    Box<[T]>,
+    std::sync::Arc<[T]>,
+    std::rc::Rc<[T]>,
+    [T]
+);
+
+impl<T> ChatMessageCollection for &T
+where
+    T: ChatMessageCollection,
+{
+    fn serialize_messages<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        <T as ChatMessageCollection>::serialize_messages(self, serializer)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        <T as ChatMessageCollection>::size_hint(self)
+    }
+}
+
+impl<T, const LEN: usize> ChatMessageCollection for [T; LEN]
+where
+    T: AsChatMessage,
+{
+    fn serialize_messages<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_slice().serialize_messages(serializer)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(LEN)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatRole {
+    System,
+    Developer,
+    User,
+    Assistant,
+    Tool,
+}
+
+contract_trait!(
+    #[impl_traits(ChatMessage, AsChatMessage)]
+    #[wrapper(System)]
+    pub trait SystemMessage {
+        content: Cow<'_, str>,
+        const "role" = "system",
+    }
+);
+
+// OpenAI's replacement for system messages on reasoning models, most local servers only know `system`
+contract_trait!(
+    #[impl_traits(ChatMessage, AsChatMessage)]
+    #[wrapper(Developer)]
+    pub trait DeveloperMessage {
+        content: Cow<'_, str>,
+        const "role" = "developer",
+    }
+);
+
+contract_trait!(
+    #[impl_traits(ChatMessage, AsChatMessage)]
+    #[wrapper(User)]
+    pub trait UserMessage {
+        content: Cow<'_, str>,
+        const "role" = "user",
+    }
+);
+
+impl UserMessage for str {
+    fn content(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl UserMessage for String {
+    fn content(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl AsChatMessage for String {
+    fn erase_variant(&self) -> &dyn ChatMessage {
+        self.as_wrapper_ref()
+    }
+}
+
+impl private::Sealed for String {}
+
+impl ChatMessage for String {
+    fn as_erased(&self) -> &dyn erased_serde::Serialize
+    where
+        Self: Sized,
+    {
+        self.as_wrapper_ref()
+    }
+}
+
+/// `{"id", "type": "function", "function": {"name", "arguments"}}`, a call made in a previous [`AssistantMessage`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(tag = "type", rename = "function")]
+pub struct ChatToolCall<'a> {
+    pub id: Cow<'a, str>,
+    pub function: ChatFunctionCall<'a>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct ChatFunctionCall<'a> {
+    pub name: Cow<'a, str>,
+    /// JSON encoded, exactly as the model produced it
+    pub arguments: Cow<'a, str>,
+}
+
+// A previous assistant turn, content is null when the turn was only tool calls
+contract_trait!(
+    #[impl_traits(ChatMessage, AsChatMessage)]
+    #[wrapper(Assistant)]
+    pub trait AssistantMessage {
+        content: Option<Cow<'_, str>>,
+        #[skip_serializing_if(Vec::is_empty)]
+        tool_calls: Vec<ChatToolCall<'_>> = Vec::new(),
+        const "role" = "assistant",
+    }
+);
+
+contract_trait!(
+    #[impl_traits(ChatMessage, AsChatMessage)]
+    #[wrapper(ToolResult)]
+    pub trait ToolMessage {
+        tool_call_id: Cow<'_, str>,
+        content: Cow<'_, str>,
+        const "role" = "tool",
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Turn {
+        text: &'static str,
+        calls: Vec<(&'static str, &'static str, &'static str)>,
+    }
+
+    impl AssistantMessage for Turn {
+        fn content(&self) -> Option<Cow<'_, str>> {
+            (!self.text.is_empty()).then_some(Cow::Borrowed(self.text))
+        }
+
+        fn tool_calls(&self) -> Vec<ChatToolCall<'_>> {
+            self.calls
+                .iter()
+                .map(|(id, name, arguments)| ChatToolCall {
+                    id: Cow::Borrowed(id),
+                    function: ChatFunctionCall {
+                        name: Cow::Borrowed(name),
+                        arguments: Cow::Borrowed(arguments),
+                    },
+                })
+                .collect()
+        }
+    }
+
+    struct CalculateResult;
+
+    impl ToolMessage for CalculateResult {
+        fn tool_call_id(&self) -> Cow<'_, str> {
+            Cow::Borrowed("call_1")
+        }
+
+        fn content(&self) -> Cow<'_, str> {
+            Cow::Borrowed("4")
+        }
+    }
+
+    enum Conversation {
+        User(String),
+        Assistant(Turn),
+        Tool(CalculateResult),
+    }
+
+    impl AsChatMessage for Conversation {
+        fn erase_variant(&self) -> &dyn ChatMessage {
+            match self {
+                Conversation::User(message) => message.erase_variant(),
+                Conversation::Assistant(turn) => turn.as_wrapper_ref(),
+                Conversation::Tool(result) => result.as_wrapper_ref(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_conversation_serializes_by_role() {
+        let messages = [
+            Conversation::User("what is 2 + 2".to_string()),
+            Conversation::Assistant(Turn {
+                text: "",
+                calls: vec![("call_1", "calculate", r#"{"expression":"2 + 2"}"#)],
+            }),
+            Conversation::Tool(CalculateResult),
+            Conversation::Assistant(Turn {
+                text: "4",
+                calls: Vec::new(),
+            }),
+        ];
+
+        let mut json = Vec::new();
+        messages
+            .serialize_messages(&mut serde_json::Serializer::new(&mut json))
+            .unwrap();
+        assert_eq!(
+            std::str::from_utf8(&json).unwrap(),
+            concat!(
+                r#"[{"content":"what is 2 + 2","role":"user"},"#,
+                r#"{"content":null,"tool_calls":[{"type":"function","id":"call_1","function":{"name":"calculate","arguments":"{\"expression\":\"2 + 2\"}"}}],"role":"assistant"},"#,
+                r#"{"tool_call_id":"call_1","content":"4","role":"tool"},"#,
+                r#"{"content":"4","role":"assistant"}]"#
+            )
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+
+use crate::openai_compat::endpoint::responses::request::{
+    openrouter::OpenRouterOptions, serialize_as_ref_str, tools::ToolCollection,
+};
+
+pub mod builder;
+pub mod message;
+pub mod tools;
+
+pub use builder::RequestBuilder;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Request<I, M, T = ()> {
+    #[serde(
+        bound(serialize = "I: message::ChatMessageCollection"),
+        serialize_with = "message::ChatMessageCollection::serialize_messages"
+    )]
+    pub messages: I,
+    #[serde(
+        bound(serialize = "M: AsRef<str>"),
+        serialize_with = "serialize_as_ref_str"
+    )]
+    pub model: M,
+    pub stream: bool,
+    /// Only allowed when streaming
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+    #[serde(
+        bound(serialize = "T: ToolCollection"),
+        serialize_with = "tools::serialize_chat_tools",
+        skip_serializing_if = "ToolCollection::is_empty"
+    )]
+    pub tools: T,
+    /// Everything else, left unset and skipped when serializing unless the builder sets it
+    #[serde(flatten)]
+    pub options: RequestOptions,
+}
+
+impl<I, M> Request<I, M> {
+    /// Start building a request, see [`RequestBuilder`] for the defaults
+    pub fn builder(messages: I, model: M) -> RequestBuilder<I, M> {
+        RequestBuilder::new(messages, model)
+    }
+}
+
+/// The optional fields of [`Request`], kept apart from the generic ones so changing the tools type doesn't have to touch them
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RequestOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_completion_tokens: Option<u64>,
+    /// Deprecated by OpenAI in favour of `max_completion_tokens`, but the only one some local servers understand
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// `models` and `provider`, which only OpenRouter understands. Nothing is sent while this is `None`.
+    #[serde(flatten)]
+    pub openrouter: Option<OpenRouterOptions>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct StreamOptions {
+    /// Send a final chunk with no choices and the usage for the whole request
+    pub include_usage: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimal_request_skips_unset_fields() {
+        let request = Request::builder(["hello".to_string()], "qwen3:8b").build();
+
+        let json = serde_json::to_string(&request).unwrap();
+        assert_eq!(
+            &json,
+            r#"{"messages":[{"content":"hello","role":"user"}],"model":"qwen3:8b","stream":true,"stream_options":{"include_usage":true}}"#
+        );
+
+        let request = Request::builder(["hello".to_string()], "qwen3:8b")
+            .stream(false)
+            .build();
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["stream"], false);
+        assert!(json.get("stream_options").is_none());
+    }
+
+    #[test]
+    fn test_builder_sets_tools() {
+        #[derive(schemars::JsonSchema)]
+        struct CalculateInput {
+            #[allow(dead_code)]
+            expression: String,
+        }
+
+        struct Calculate;
+
+        impl crate::tool::Tool for Calculate {
+            type Input = CalculateInput;
+
+            fn name(&self) -> &str {
+                "calculate"
+            }
+
+            fn description(&self) -> &str {
+                "Perform a mathematical calculation"
+            }
+        }
+
+        let request = Request::builder(Vec::<String>::new(), "qwen3:8b")
+            .tools([Calculate])
+            .max_tokens(512)
+            .stop(["</answer>"])
+            .build();
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["tools"][0]["type"], "function");
+        assert_eq!(json["tools"][0]["function"]["name"], "calculate");
+        assert_eq!(
+            json["tools"][0]["function"]["parameters"]["properties"]["expression"]["type"],
+            "string"
+        );
+        assert!(json["tools"][0].get("name").is_none());
+        assert_eq!(json["max_tokens"], 512);
+        assert_eq!(json["stop"][0], "</answer>");
+    }
+}
@@ -0,0 +1,52 @@
+//! Serialization of [`Tool`](crate::tool::Tool)s into the chat completions `tools` array, taking the same [`ToolCollection`]s as the responses request
+
+use serde::{Serialize, Serializer, ser::SerializeMap};
+
+use crate::{
+    openai_compat::endpoint::responses::request::tools::{FunctionStr, ToolCollection},
+    tool::ToolDefinition,
+};
+
+/// `serialize_with` for the chat completions `tools` field, every tool of the collection as a [`ChatFunctionTool`]
+pub fn serialize_chat_tools<T, S>(tools: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ToolCollection,
+    S: Serializer,
+{
+    serializer.collect_seq(tools.tool_definitions().map(ChatFunctionTool))
+}
+
+/// A tool definition in the shape the chat completions endpoint expects, `{"type": "function", "function": {"name", "description", "parameters", "strict"}}`
+#[derive(Clone, Copy)]
+pub struct ChatFunctionTool<'a>(pub &'a dyn ToolDefinition);
+
+/// The inner `function` object of a [`ChatFunctionTool`]
+struct ChatFunctionDefinition<'a>(&'a dyn ToolDefinition);
+
+impl Serialize for ChatFunctionTool<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("type", &FunctionStr)?;
+        map.serialize_entry("function", &ChatFunctionDefinition(self.0))?;
+        map.end()
+    }
+}
+
+impl Serialize for ChatFunctionDefinition<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(4))?;
+        map.serialize_entry("name", self.0.name())?;
+        map.serialize_entry("description", self.0.description())?;
+        map.serialize_entry("parameters", &self.0.parameters())?;
+        map.serialize_entry("strict", &self.0.strict())?;
+        map.end()
+    }
+}
+
+impl std::fmt::Debug for ChatFunctionTool<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChatFunctionTool")
+            .field("name", &self.0.name())
+            .finish_non_exhaustive()
+    }
+}
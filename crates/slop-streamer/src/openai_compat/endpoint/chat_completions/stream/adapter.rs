@@ -0,0 +1,751 @@
+//! Synthesizes the responses event sequence out of chat completion chunks, so code written against [`StreamEvent`] can be pointed at servers that only speak chat completions
+//!
+//! Only the first choice is translated, responses has no equivalent of `n > 1`.
+//! Item ids are derived from the chunk id and the output index, so translating the same chunks twice gives the same events.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes_utils::Str;
+use futures::Stream;
+
+use crate::openai_compat::endpoint::{
+    chat_completions::stream::chunk::{ChatCompletionChunk, ChatUsage, FinishReason},
+    responses::{
+        response::Response,
+        stream::stream_item::{
+            ContentPart, ContentPartAddedData, ContentPartDoneData, FunctionCallArgumentsDeltaData,
+            FunctionCallArgumentsDoneData, FunctionCallItem, IncompleteDetails, ItemStatus,
+            MessageItem, OutputItem, OutputItemAddedData, OutputItemDoneData, OutputTextDeltaData,
+            OutputTextDoneData, OutputTextPart, ReasoningItem, ReasoningTextDeltaData,
+            ReasoningTextDoneData, ReasoningTextPart, ResponseCompletedData, ResponseCreatedData,
+            ResponseInProgressData, ResponseIncompleteData, ResponseStatus, StreamEvent, UsageInfo,
+        },
+    },
+};
+
+/// Turns [`ChatCompletionChunk`]s into [`StreamEvent`]s, see [`ResponsesEventStream`] to drive one from a stream directly
+#[derive(Debug, Clone, Default)]
+pub struct ChunkTranslator {
+    sequence_number: u64,
+    /// The first chunk's id, `None` until a chunk has been pushed
+    id: Option<Str>,
+    model: Option<Str>,
+    created_at: Option<u64>,
+    /// Every item opened so far, the position is the `output_index`
+    output: Vec<PendingItem>,
+    /// The message or reasoning item text deltas are currently going into
+    open_text: Option<usize>,
+    /// The output index of each tool call, keyed by the chunk's tool call `index`
+    tool_calls: BTreeMap<u32, usize>,
+    finish_reason: Option<FinishReason>,
+    usage: Option<UsageInfo>,
+}
+
+#[derive(Debug, Clone)]
+struct PendingItem {
+    id: Str,
+    kind: PendingKind,
+    done: bool,
+}
+
+#[derive(Debug, Clone)]
+enum PendingKind {
+    Message {
+        text: String,
+    },
+    Reasoning {
+        text: String,
+    },
+    FunctionCall {
+        call_id: String,
+        name: String,
+        arguments: String,
+    },
+}
+
+impl ChunkTranslator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The events for a single chunk, `response.created` and `response.in_progress` come before the first chunk's
+    pub fn push(&mut self, chunk: &ChatCompletionChunk) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+
+        if self.id.is_none() {
+            self.id = Some(chunk.id.clone());
+            self.model = chunk.model.clone();
+            self.created_at = chunk.created;
+
+            let response = self.response(ResponseStatus::InProgress);
+            events.push(StreamEvent::ResponseCreated(ResponseCreatedData {
+                response: response.clone(),
+                sequence_number: self.next_sequence_number(),
+            }));
+            events.push(StreamEvent::ResponseInProgress(ResponseInProgressData {
+                response: Some(response),
+                sequence_number: self.next_sequence_number(),
+            }));
+        }
+
+        if let Some(usage) = &chunk.usage {
+            self.usage = Some(usage_info(usage));
+        }
+
+        let Some(choice) = chunk.choices.iter().find(|choice| choice.index == 0) else {
+            return events;
+        };
+
+        if let Some(delta) = choice
+            .delta
+            .reasoning_text()
+            .filter(|delta| !delta.is_empty())
+        {
+            let idx = self.open_text_item(&mut events, true);
+            self.push_text(&mut events, idx, delta);
+        }
+
+        if let Some(delta) = choice
+            .delta
+            .content
+            .as_ref()
+            .filter(|delta| !delta.is_empty())
+        {
+            let idx = self.open_text_item(&mut events, false);
+            self.push_text(&mut events, idx, delta);
+        }
+
+        for call in &choice.delta.tool_calls {
+            let idx = match self.tool_calls.get(&call.index) {
+                Some(&idx) => idx,
+                None => {
+                    if let Some(open) = self.open_text.take() {
+                        self.close(&mut events, open);
+                    }
+
+                    let idx = self.output.len();
+                    let call_id = match &call.id {
+                        Some(id) => id.to_string(),
+                        None => format!("call_{}_{}", self.response_id(), call.index),
+                    };
+                    let name = call
+                        .function
+                        .as_ref()
+                        .and_then(|function| function.name.as_deref())
+                        .unwrap_or_default()
+                        .to_string();
+                    self.open(
+                        &mut events,
+                        "fc",
+                        PendingKind::FunctionCall {
+                            call_id,
+                            name,
+                            arguments: String::new(),
+                        },
+                    );
+                    self.tool_calls.insert(call.index, idx);
+                    self.push_arguments(
+                        &mut events,
+                        idx,
+                        call.function.as_ref().and_then(|f| f.arguments.as_ref()),
+                    );
+                    continue;
+                }
+            };
+
+            if let Some(function) = &call.function {
+                // Only the first piece should carry the name, but some servers repeat it on every piece and others split it up.
+                // A piece that starts with what we have is a repeat (or a longer repeat) of it, anything else is the next
+                // fragment, which can only come before the arguments start
+                if let Some(more) = &function.name
+                    && let PendingKind::FunctionCall {
+                        name, arguments, ..
+                    } = &mut self.output[idx].kind
+                {
+                    if more.starts_with(name.as_str()) {
+                        name.replace_range(.., more);
+                    } else if arguments.is_empty() {
+                        name.push_str(more);
+                    }
+                }
+                self.push_arguments(&mut events, idx, function.arguments.as_ref());
+            }
+        }
+
+        if let Some(finish_reason) = choice.finish_reason {
+            self.finish_reason = Some(finish_reason);
+            self.close_all(&mut events);
+        }
+
+        events
+    }
+
+    /// The events for the end of the stream, closing anything still open and then `response.completed`,
+    /// or `response.incomplete` if the model was cut off. Nothing if no chunk was ever pushed.
+    pub fn finish(&mut self) -> Vec<StreamEvent> {
+        let mut events = Vec::new();
+        if self.id.is_none() {
+            return events;
+        }
+
+        self.close_all(&mut events);
+
+        let incomplete_reason = match self.finish_reason {
+            Some(FinishReason::Length) => Some("max_output_tokens"),
+            Some(FinishReason::ContentFilter) => Some("content_filter"),
+            _ => None,
+        };
+        let status = match incomplete_reason {
+            Some(_) => ResponseStatus::Incomplete,
+            None => ResponseStatus::Completed,
+        };
+
+        let mut response = self.response(status);
+        response.output = self
+            .output
+            .iter()
+            .map(PendingItem::to_output_item)
+            .collect();
+        response.usage = self.usage.clone();
+        response.incomplete_details = incomplete_reason.map(|reason| IncompleteDetails {
+            reason: Str::from_static(reason),
+        });
+
+        let sequence_number = self.next_sequence_number();
+        events.push(match status {
+            ResponseStatus::Incomplete => StreamEvent::ResponseIncomplete(ResponseIncompleteData {
+                response,
+                sequence_number,
+            }),
+            _ => StreamEvent::ResponseCompleted(ResponseCompletedData {
+                response,
+                sequence_number,
+            }),
+        });
+
+        events
+    }
+
+    fn next_sequence_number(&mut self) -> u64 {
+        let sequence_number = self.sequence_number;
+        self.sequence_number += 1;
+        sequence_number
+    }
+
+    fn response_id(&self) -> &str {
+        self.id.as_deref().unwrap_or_default()
+    }
+
+    fn response(&self, status: ResponseStatus) -> Response {
+        let mut response = Response::new(self.id.clone().unwrap_or_default(), status);
+        response.model = self.model.clone();
+        response.created_at = self.created_at;
+        response
+    }
+
+    /// The open message (or reasoning) item, closing the other kind first if that's what's open
+    fn open_text_item(&mut self, events: &mut Vec<StreamEvent>, reasoning: bool) -> usize {
+        if let Some(open) = self.open_text {
+            if matches!(self.output[open].kind, PendingKind::Reasoning { .. }) == reasoning {
+                return open;
+            }
+            self.close(events, open);
+        }
+
+        let idx = if reasoning {
+            self.open(
+                events,
+                "rs",
+                PendingKind::Reasoning {
+                    text: String::new(),
+                },
+            )
+        } else {
+            self.open(
+                events,
+                "msg",
+                PendingKind::Message {
+                    text: String::new(),
+                },
+            )
+        };
+        self.open_text = Some(idx);
+
+        let item = &self.output[idx];
+        let part = item.content_part();
+        let item_id = item.id.clone();
+        let sequence_number = self.next_sequence_number();
+        events.push(StreamEvent::ResponseContentPartAdded(
+            ContentPartAddedData {
+                item_id,
+                output_index: idx as u32,
+                content_index: 0,
+                part,
+                sequence_number,
+            },
+        ));
+
+        idx
+    }
+
+    fn open(&mut self, events: &mut Vec<StreamEvent>, prefix: &str, kind: PendingKind) -> usize {
+        let idx = self.output.len();
+        let item = PendingItem {
+            id: Str::from(format!("{}_{}_{}", prefix, self.response_id(), idx)),
+            kind,
+            done: false,
+        };
+        let added = item.to_output_item();
+        self.output.push(item);
+
+        let sequence_number = self.next_sequence_number();
+        events.push(StreamEvent::ResponseOutputItemAdded(OutputItemAddedData {
+            output_index: idx as u32,
+            item: added,
+            sequence_number,
+        }));
+        idx
+    }
+
+    fn push_text(&mut self, events: &mut Vec<StreamEvent>, idx: usize, delta: &Str) {
+        let item_id = self.output[idx].id.clone();
+        let sequence_number = self.next_sequence_number();
+        let output_index = idx as u32;
+        let delta = delta.clone();
+
+        match &mut self.output[idx].kind {
+            PendingKind::Message { text } => {
+                text.push_str(&delta);
+                events.push(StreamEvent::ResponseOutputTextDelta(OutputTextDeltaData {
+                    output_index,
+                    item_id,
+                    content_index: 0,
+                    delta,
+                    sequence_number,
+                }));
+            }
+            PendingKind::Reasoning { text } => {
+                text.push_str(&delta);
+                events.push(StreamEvent::ResponseReasoningTextDelta(
+                    ReasoningTextDeltaData {
+                        output_index,
+                        item_id,
+                        content_index: 0,
+                        delta,
+                        sequence_number,
+                    },
+                ));
+            }
+            PendingKind::FunctionCall { .. } => unreachable!("text deltas only go to text items"),
+        }
+    }
+
+    fn push_arguments(&mut self, events: &mut Vec<StreamEvent>, idx: usize, delta: Option<&Str>) {
+        let Some(delta) = delta.filter(|delta| !delta.is_empty()) else {
+            return;
+        };
+        if let PendingKind::FunctionCall { arguments, .. } = &mut self.output[idx].kind {
+            arguments.push_str(delta);
+        }
+
+        let item_id = self.output[idx].id.clone();
+        let sequence_number = self.next_sequence_number();
+        events.push(StreamEvent::ResponseFunctionCallArgumentsDelta(
+            FunctionCallArgumentsDeltaData {
+                item_id,
+                output_index: idx as u32,
+                delta: delta.clone(),
+                sequence_number,
+            },
+        ));
+    }
+
+    fn close_all(&mut self, events: &mut Vec<StreamEvent>) {
+        self.open_text = None;
+        for idx in 0..self.output.len() {
+            self.close(events, idx);
+        }
+    }
+
+    /// The `.done` events for an item, in the order OpenAI sends them
+    fn close(&mut self, events: &mut Vec<StreamEvent>, idx: usize) {
+        if self.output[idx].done {
+            return;
+        }
+        self.output[idx].done = true;
+        if self.open_text == Some(idx) {
+            self.open_text = None;
+        }
+
+        let item = self.output[idx].clone();
+        let output_index = idx as u32;
+        let item_id = item.id.clone();
+
+        match &item.kind {
+            PendingKind::Message { text } => {
+                let sequence_number = self.next_sequence_number();
+                events.push(StreamEvent::ResponseOutputTextDone(OutputTextDoneData {
+                    item_id: item_id.clone(),
+                    output_index,
+                    content_index: 0,
+                    text: Str::from(text.clone()),
+                    sequence_number,
+                }));
+            }
+            PendingKind::Reasoning { text } => {
+                let sequence_number = self.next_sequence_number();
+                events.push(StreamEvent::ResponseReasoningTextDone(
+                    ReasoningTextDoneData {
+                        output_index,
+                        item_id: item_id.clone(),
+                        content_index: 0,
+                        text: Str::from(text.clone()),
+                        sequence_number,
+                    },
+                ));
+            }
+            PendingKind::FunctionCall {
+                name, arguments, ..
+            } => {
+                let sequence_number = self.next_sequence_number();
+                events.push(StreamEvent::ResponseFunctionCallArgumentsDone(
+                    FunctionCallArgumentsDoneData {
+                        item_id: item_id.clone(),
+                        output_index,
+                        name: Str::from(name.clone()),
+                        arguments: Str::from(arguments.clone()),
+                        sequence_number,
+                    },
+                ));
+            }
+        }
+
+        if !matches!(item.kind, PendingKind::FunctionCall { .. }) {
+            let sequence_number = self.next_sequence_number();
+            events.push(StreamEvent::ResponseContentPartDone(ContentPartDoneData {
+                item_id,
+                output_index,
+                content_index: 0,
+                part: item.content_part(),
+                sequence_number,
+            }));
+        }
+
+        let sequence_number = self.next_sequence_number();
+        events.push(StreamEvent::ResponseOutputItemDone(OutputItemDoneData {
+            output_index,
+            item: item.to_output_item(),
+            sequence_number,
+        }));
+    }
+}
+
+impl PendingItem {
+    /// The item as it stands, empty until closed for the text items since that's how the added events carry them
+    fn to_output_item(&self) -> OutputItem {
+        let status = match self.done {
+            true => ItemStatus::Completed,
+            false => ItemStatus::InProgress,
+        };
+        match &self.kind {
+            PendingKind::Message { .. } => OutputItem::Message(MessageItem {
+                id: self.id.clone(),
+                status,
+                content: match self.done {
+                    true => vec![self.content_part()],
+                    false => Vec::new(),
+                },
+            }),
+            PendingKind::Reasoning { .. } => OutputItem::Reasoning(ReasoningItem {
+                id: self.id.clone(),
+                summary: Vec::new(),
+                content: match self.done {
+                    true => vec![self.content_part()],
+                    false => Vec::new(),
+                },
+                encrypted_content: None,
+            }),
+            PendingKind::FunctionCall {
+                call_id,
+                name,
+                arguments,
+            } => OutputItem::FunctionCall(FunctionCallItem {
+                id: Some(self.id.clone()),
+                call_id: Str::from(call_id.clone()),
+                name: Str::from(name.clone()),
+                arguments: match self.done {
+                    true => Str::from(arguments.clone()),
+                    false => Str::default(),
+                },
+                status,
+            }),
+        }
+    }
+
+    /// The single content part of a text item, with the text so far
+    fn content_part(&self) -> ContentPart {
+        match &self.kind {
+            PendingKind::Message { text } => ContentPart::OutputText(OutputTextPart {
+                text: Str::from(text.clone()),
+                annotations: Vec::new(),
+            }),
+            PendingKind::Reasoning { text } => ContentPart::ReasoningText(ReasoningTextPart {
+                text: Str::from(text.clone()),
+            }),
+            PendingKind::FunctionCall { .. } => {
+                unreachable!("function calls have no content parts")
+            }
+        }
+    }
+}
+
+fn usage_info(usage: &ChatUsage) -> UsageInfo {
+    UsageInfo {
+        input_tokens: usage.prompt_tokens,
+        output_tokens: usage.completion_tokens,
+        total_tokens: usage.total_tokens,
+        input_tokens_details: usage.prompt_tokens_details,
+        output_tokens_details: usage.completion_tokens_details,
+        cost: usage.cost,
+        is_byok: usage.is_byok,
+        cost_details: usage.cost_details,
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Adapts a stream of [`ChatCompletionChunk`]s (usually a [`ChatCompletionsStream`](super::ChatCompletionsStream)) into one of [`StreamEvent`]s.
+    /// Errors are passed through as they are, and once one has been the stream ends without a `response.completed`.
+    #[derive(Debug)]
+    pub struct ResponsesEventStream<S> {
+        #[pin]
+        stream: S,
+        translator: ChunkTranslator,
+        pending: VecDeque<StreamEvent>,
+        failed: bool,
+        finished: bool,
+    }
+}
+
+impl<S> ResponsesEventStream<S> {
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            translator: ChunkTranslator::new(),
+            pending: VecDeque::new(),
+            failed: false,
+            finished: false,
+        }
+    }
+}
+
+impl<S, E> Stream for ResponsesEventStream<S>
+where
+    S: Stream<Item = Result<ChatCompletionChunk, E>>,
+{
+    type Item = Result<StreamEvent, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            if let Some(event) = this.pending.pop_front() {
+                return Poll::Ready(Some(Ok(event)));
+            }
+            if *this.finished {
+                return Poll::Ready(None);
+            }
+
+            match futures::ready!(this.stream.as_mut().poll_next(cx)) {
+                Some(Ok(chunk)) => this.pending.extend(this.translator.push(&chunk)),
+                Some(Err(err)) => {
+                    *this.failed = true;
+                    return Poll::Ready(Some(Err(err)));
+                }
+                None => {
+                    *this.finished = true;
+                    if !*this.failed {
+                        this.pending.extend(this.translator.finish());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "miri"))] // These tests don't work with miri
+mod tests {
+    use std::convert::Infallible;
+
+    use bytes::Bytes;
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::openai_compat::endpoint::{
+        chat_completions::stream::{ChatCompletionsStream, tests::TOOL_CALL_STREAM},
+        responses::stream::accumulator::ResponseAccumulator,
+    };
+
+    async fn translate(body: &'static str) -> Vec<StreamEvent> {
+        let chunks =
+            futures::stream::iter([Ok::<_, Infallible>(Bytes::from_static(body.as_bytes()))]);
+        ResponsesEventStream::new(ChatCompletionsStream::new(chunks))
+            .map(Result::unwrap)
+            .collect()
+            .await
+    }
+
+    fn event_type(event: &StreamEvent) -> String {
+        serde_json::to_value(event).unwrap()["type"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_event_sequence() {
+        let events = translate(TOOL_CALL_STREAM).await;
+
+        assert_eq!(
+            events.iter().map(event_type).collect::<Vec<_>>(),
+            [
+                "response.created",
+                "response.in_progress",
+                "response.output_item.added",
+                "response.content_part.added",
+                "response.reasoning_text.delta",
+                "response.reasoning_text.delta",
+                "response.reasoning_text.done",
+                "response.content_part.done",
+                "response.output_item.done",
+                "response.output_item.added",
+                "response.function_call_arguments.delta",
+                "response.function_call_arguments.delta",
+                "response.function_call_arguments.done",
+                "response.output_item.done",
+                "response.completed",
+            ]
+        );
+
+        let sequence_numbers = events
+            .iter()
+            .map(|event| {
+                serde_json::to_value(event).unwrap()["sequence_number"]
+                    .as_u64()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sequence_numbers,
+            (0..events.len() as u64).collect::<Vec<_>>()
+        );
+
+        let mut accumulator = ResponseAccumulator::new();
+        for event in &events {
+            accumulator.push(event);
+        }
+        let snapshot = accumulator.into_snapshot();
+        assert!(snapshot.is_completed());
+        assert_eq!(snapshot.id.as_deref(), Some("chatcmpl-1"));
+        let usage = snapshot.usage.as_ref().unwrap();
+        assert_eq!(usage.input_tokens, 152);
+        assert_eq!(usage.cached_tokens(), 128);
+        assert_eq!(usage.reasoning_tokens(), 12);
+
+        let call = snapshot.function_calls().next().unwrap();
+        assert_eq!(call.id.as_deref(), Some("fc_chatcmpl-1_1"));
+        assert_eq!(call.call_id, "call_a");
+        assert_eq!(call.name, "get_current_weather");
+        assert_eq!(call.arguments, r#"{"location": "Tokyo, Japan"}"#);
+        assert_eq!(call.status, ItemStatus::Completed);
+
+        let StreamEvent::ResponseCompleted(completed) = events.last().unwrap() else {
+            panic!("expected response.completed");
+        };
+        assert_eq!(completed.response.output.len(), 2);
+
+        // Same chunks, same events
+        assert_eq!(translate(TOOL_CALL_STREAM).await, events);
+    }
+
+    #[tokio::test]
+    async fn test_interleaved_calls_and_truncated_text() {
+        let body = concat!(
+            "data: {\"id\":\"gen-2\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"Checking \"}}]}\n\n",
+            "data: {\"id\":\"gen-2\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_x\",\"function\":{\"name\":\"a\",\"arguments\":\"{\\\"n\\\":\"}},{\"index\":1,\"function\":{\"name\":\"b\",\"arguments\":\"{}\"}}]}}]}\n\n",
+            "data: {\"id\":\"gen-2\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"1}\"}}]}}]}\n\n",
+            "data: {\"id\":\"gen-2\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"both\"},\"finish_reason\":\"length\"}]}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let events = translate(body).await;
+
+        let mut accumulator = ResponseAccumulator::new();
+        for event in &events {
+            accumulator.push(event);
+        }
+        let snapshot = accumulator.into_snapshot();
+        assert_eq!(snapshot.status, Some(ResponseStatus::Incomplete));
+        assert_eq!(
+            snapshot.incomplete_details.as_ref().unwrap().reason,
+            "max_output_tokens"
+        );
+        assert_eq!(snapshot.text(), "Checking both");
+
+        let calls = snapshot
+            .function_calls()
+            .map(|call| {
+                (
+                    call.call_id.as_str(),
+                    call.name.as_str(),
+                    call.arguments.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            calls,
+            [("call_x", "a", r#"{"n":1}"#), ("call_gen-2_1", "b", "{}")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_name_fragments() {
+        let body = concat!(
+            // split up, and then repeated whole with the arguments
+            "data: {\"id\":\"gen-3\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_s\",\"function\":{\"name\":\"get_\"}}]}}]}\n\n",
+            "data: {\"id\":\"gen-3\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"name\":\"weather\"}}]}}]}\n\n",
+            "data: {\"id\":\"gen-3\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"name\":\"get_weather\",\"arguments\":\"{}\"}}]}}]}\n\n",
+            // each piece repeating the name so far
+            "data: {\"id\":\"gen-3\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":1,\"id\":\"call_r\",\"function\":{\"name\":\"get_\"}}]}}]}\n\n",
+            "data: {\"id\":\"gen-3\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":1,\"function\":{\"name\":\"get_weather\",\"arguments\":\"{\\\"city\\\":\"}}]}}]}\n\n",
+            "data: {\"id\":\"gen-3\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":1,\"function\":{\"name\":\"get_weather\",\"arguments\":\"\\\"Oslo\\\"}\"}}]},\"finish_reason\":\"tool_calls\"}]}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let events = translate(body).await;
+
+        let mut accumulator = ResponseAccumulator::new();
+        for event in &events {
+            accumulator.push(event);
+        }
+        let snapshot = accumulator.into_snapshot();
+        let calls = snapshot
+            .function_calls()
+            .map(|call| {
+                (
+                    call.call_id.as_str(),
+                    call.name.as_str(),
+                    call.arguments.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            calls,
+            [
+                ("call_s", "get_weather", "{}"),
+                ("call_r", "get_weather", r#"{"city":"Oslo"}"#)
+            ]
+        );
+    }
+}
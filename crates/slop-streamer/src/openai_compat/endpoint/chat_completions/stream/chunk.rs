@@ -0,0 +1,159 @@
+//! `chat.completion.chunk` objects, one per event.
+//! Generic over the string type the same way as the responses [`StreamEvent`](crate::openai_compat::endpoint::responses::stream::stream_item::StreamEvent)s,
+//! parsed as `Cow<str>` then sliced out of the event buffer as [`Str`].
+
+use std::borrow::Cow;
+
+use bytes_utils::Str;
+use serde::{Deserialize, Serialize};
+
+use crate::openai_compat::{
+    api_error::ApiError,
+    endpoint::{
+        chat_completions::request::message::ChatRole,
+        responses::stream::stream_item::{
+            ConvertToOwned, ConvertToString, CostDetails, InputTokensDetails, OutputTokensDetails,
+            impl_conversion,
+        },
+    },
+    serde_helpers::null_as_default,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatCompletionChunk<T = Str> {
+    pub id: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<T>,
+    /// Empty on the usage chunk sent at the end when `stream_options.include_usage` is set
+    #[serde(default = "Vec::new", deserialize_with = "null_as_default")]
+    pub choices: Vec<ChunkChoice<T>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<ChatUsage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_fingerprint: Option<T>,
+    /// OpenRouter puts the error on an otherwise normal chunk (with a `finish_reason` of `error`) when the upstream fails mid-stream
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ApiError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkChoice<T = Str> {
+    #[serde(default)]
+    pub index: u32,
+    pub delta: ChunkDelta<T>,
+    /// Only set on the last chunk for this choice
+    pub finish_reason: Option<FinishReason>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkDelta<T = Str> {
+    /// Only on the first chunk
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<ChatRole>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<T>,
+    /// What vLLM, llama.cpp and DeepSeek call the reasoning
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_content: Option<T>,
+    /// What OpenRouter and Ollama call the reasoning, see [`ChunkDelta::reasoning_text`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refusal: Option<T>,
+    #[serde(
+        default = "Vec::new",
+        deserialize_with = "null_as_default",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub tool_calls: Vec<ToolCallDelta<T>>,
+}
+
+impl<T> ChunkDelta<T> {
+    /// The reasoning under whichever name the server used for it
+    pub fn reasoning_text(&self) -> Option<&T> {
+        self.reasoning_content.as_ref().or(self.reasoning.as_ref())
+    }
+}
+
+/// A piece of a tool call, `id` and `name` come on the first piece for an `index` and `arguments` is split across all of them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCallDelta<T = Str> {
+    /// Which call in the message this belongs to, pieces of parallel calls can be interleaved
+    #[serde(default)]
+    pub index: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<FunctionCallDelta<T>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionCallDelta<T = Str> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<T>,
+    /// A fragment of the JSON encoded arguments, only valid JSON once every fragment is concatenated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<T>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinishReason {
+    Stop,
+    Length,
+    ToolCalls,
+    ContentFilter,
+    /// The legacy `function_call` form of [`FinishReason::ToolCalls`]
+    FunctionCall,
+    /// OpenRouter, see [`ChatCompletionChunk::error`]
+    Error,
+    /// Anything else a server made up
+    #[serde(other)]
+    Other,
+}
+
+impl std::fmt::Display for FinishReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FinishReason::Stop => "stop",
+            FinishReason::Length => "length",
+            FinishReason::ToolCalls => "tool_calls",
+            FinishReason::ContentFilter => "content_filter",
+            FinishReason::FunctionCall => "function_call",
+            FinishReason::Error => "error",
+            FinishReason::Other => "other",
+        }
+        .fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens_details: Option<InputTokensDetails>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion_tokens_details: Option<OutputTokensDetails>,
+    /// OpenRouter only, in credits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_byok: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_details: Option<CostDetails>,
+}
+
+impl_conversion!(ChatRole);
+impl_conversion!(FinishReason);
+impl_conversion!(ChatUsage);
+impl_conversion!(ApiError);
+
+impl_conversion!(ChatCompletionChunk struct [id, model, choices, system_fingerprint] [created, usage, error]);
+impl_conversion!(ChunkChoice struct [delta] [index, finish_reason]);
+impl_conversion!(ChunkDelta struct [content, reasoning_content, reasoning, refusal, tool_calls] [role]);
+impl_conversion!(ToolCallDelta struct [id, function] [index]);
+impl_conversion!(FunctionCallDelta struct [name, arguments] []);
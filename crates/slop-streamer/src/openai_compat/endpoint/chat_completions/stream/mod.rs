@@ -0,0 +1,273 @@
+use std::{
+    borrow::Cow,
+    pin::Pin,
+    str::Utf8Error,
+    task::{Context, Poll},
+};
+
+use futures::Stream;
+use sseer::errors::EventStreamError;
+
+use crate::openai_compat::{
+    api_error::ApiError,
+    endpoint::{
+        chat_completions::stream::{adapter::ResponsesEventStream, chunk::ChatCompletionChunk},
+        responses::stream::stream_item::ConvertToOwned,
+    },
+};
+
+pub mod adapter;
+pub mod chunk;
+
+pin_project_lite::pin_project! {
+    /// Parses a chat completions SSE body into [`ChatCompletionChunk`]s, ending at `[DONE]`
+    #[project = ChatCompletionsStreamProjection]
+    #[derive(Debug)]
+    pub struct ChatCompletionsStream<S> {
+        #[pin]
+        state: ChatCompletionsStreamState<S>,
+    }
+}
+
+impl<S> ChatCompletionsStream<S> {
+    pub fn new(stream: S) -> Self
+    where
+        S: Stream,
+    {
+        Self {
+            state: ChatCompletionsStreamState::Active {
+                stream: sseer::EventStream::new(stream),
+            },
+        }
+    }
+
+    /// Translate the chunks into responses [`StreamEvent`](crate::openai_compat::endpoint::responses::stream::stream_item::StreamEvent)s, see [`ResponsesEventStream`]
+    pub fn into_responses_events(self) -> ResponsesEventStream<Self> {
+        ResponsesEventStream::new(self)
+    }
+}
+
+pin_project_lite::pin_project! {
+    #[derive(Debug)]
+    #[project = ChatCompletionsStreamStateProjection]
+    enum ChatCompletionsStreamState<S> {
+        Active {
+            #[pin]
+            stream: sseer::EventStream<S>
+        },
+        Terminated
+    }
+}
+
+#[derive(Debug)]
+pub enum ChatCompletionsStreamError<E> {
+    Transport(E),
+    Utf8Error(Utf8Error),
+    Deserialize(serde_json::Error),
+    /// The provider sent an error instead of a chunk, either as a bare `{"error": {...}}` envelope or on a chunk finishing with `error`.
+    /// The stream ends after it.
+    Api(ApiError),
+}
+
+impl<E> ChatCompletionsStreamError<E> {
+    pub fn map_transport<F>(self, f: impl Fn(E) -> F) -> ChatCompletionsStreamError<F> {
+        match self {
+            ChatCompletionsStreamError::Transport(e) => ChatCompletionsStreamError::Transport(f(e)),
+            ChatCompletionsStreamError::Utf8Error(e) => ChatCompletionsStreamError::Utf8Error(e),
+            ChatCompletionsStreamError::Deserialize(e) => {
+                ChatCompletionsStreamError::Deserialize(e)
+            }
+            ChatCompletionsStreamError::Api(error) => ChatCompletionsStreamError::Api(error),
+        }
+    }
+}
+
+impl<E> From<serde_json::Error> for ChatCompletionsStreamError<E> {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Deserialize(value)
+    }
+}
+
+impl<E> From<EventStreamError<E>> for ChatCompletionsStreamError<E> {
+    fn from(value: EventStreamError<E>) -> Self {
+        match value {
+            EventStreamError::Transport(e) => Self::Transport(e),
+            EventStreamError::Utf8Error(e) => Self::Utf8Error(e),
+        }
+    }
+}
+
+impl<E> std::fmt::Display for ChatCompletionsStreamError<E>
+where
+    E: std::fmt::Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChatCompletionsStreamError::Transport(e) => e.fmt(f),
+            ChatCompletionsStreamError::Utf8Error(utf8_error) => utf8_error.fmt(f),
+            ChatCompletionsStreamError::Deserialize(error) => error.fmt(f),
+            ChatCompletionsStreamError::Api(error) => error.fmt(f),
+        }
+    }
+}
+
+impl<E> std::error::Error for ChatCompletionsStreamError<E> where E: std::error::Error {}
+
+impl<S, B, E> Stream for ChatCompletionsStream<S>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+{
+    type Item = Result<ChatCompletionChunk, ChatCompletionsStreamError<E>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        let stream = match this.state.as_mut().project() {
+            ChatCompletionsStreamStateProjection::Active { stream } => stream,
+            ChatCompletionsStreamStateProjection::Terminated => return Poll::Ready(None),
+        };
+
+        let ev = match futures::ready!(stream.poll_next(cx)) {
+            Some(Ok(ev)) => ev,
+            Some(Err(err)) => {
+                return Poll::Ready(Some(Err(err.into())));
+            }
+            None => {
+                this.state.set(ChatCompletionsStreamState::Terminated);
+                return Poll::Ready(None);
+            }
+        };
+
+        if ev.data == "[DONE]" {
+            this.state.set(ChatCompletionsStreamState::Terminated);
+            return Poll::Ready(None);
+        }
+
+        let mut chunk = match serde_json::from_str::<ChatCompletionChunk<Cow<'_, str>>>(&ev.data) {
+            Ok(borrowed) => borrowed.convert_to_owned(&ev.data),
+            Err(err) => {
+                return Poll::Ready(Some(Err(match ApiError::from_body(ev.data.as_bytes()) {
+                    Some(error) => {
+                        this.state.set(ChatCompletionsStreamState::Terminated);
+                        ChatCompletionsStreamError::Api(error)
+                    }
+                    None => err.into(),
+                })));
+            }
+        };
+
+        if let Some(error) = chunk.error.take() {
+            this.state.set(ChatCompletionsStreamState::Terminated);
+            return Poll::Ready(Some(Err(ChatCompletionsStreamError::Api(error))));
+        }
+
+        Poll::Ready(Some(Ok(chunk)))
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "miri"))] // These tests don't work with miri
+mod tests {
+    use std::convert::Infallible;
+
+    use bytes::Bytes;
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::openai_compat::endpoint::{
+        chat_completions::{request::message::ChatRole, stream::chunk::FinishReason},
+        responses::stream::stream_item::ConvertToString,
+    };
+
+    // vLLM with a reasoning parser and tool calling enabled, trimmed
+    pub(super) const TOOL_CALL_STREAM: &str = concat!(
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1769599723,\"model\":\"Qwen/Qwen3-8B\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"\"},\"logprobs\":null,\"finish_reason\":null}]}\n\n",
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1769599723,\"model\":\"Qwen/Qwen3-8B\",\"choices\":[{\"index\":0,\"delta\":{\"reasoning_content\":\"Need the \"},\"finish_reason\":null}]}\n\n",
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1769599723,\"model\":\"Qwen/Qwen3-8B\",\"choices\":[{\"index\":0,\"delta\":{\"reasoning_content\":\"weather.\"},\"finish_reason\":null}]}\n\n",
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1769599723,\"model\":\"Qwen/Qwen3-8B\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"id\":\"call_a\",\"type\":\"function\",\"index\":0,\"function\":{\"name\":\"get_current_weather\",\"arguments\":\"\"}}]},\"finish_reason\":null}]}\n\n",
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1769599723,\"model\":\"Qwen/Qwen3-8B\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"{\\\"location\\\": \"}}]},\"finish_reason\":null}]}\n\n",
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1769599723,\"model\":\"Qwen/Qwen3-8B\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"\\\"Tokyo, Japan\\\"}\"}}]},\"finish_reason\":null}]}\n\n",
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1769599723,\"model\":\"Qwen/Qwen3-8B\",\"choices\":[{\"index\":0,\"delta\":{\"content\":null,\"tool_calls\":null},\"finish_reason\":\"tool_calls\"}]}\n\n",
+        "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":1769599723,\"model\":\"Qwen/Qwen3-8B\",\"choices\":[],\"usage\":{\"prompt_tokens\":152,\"total_tokens\":189,\"completion_tokens\":37,\"prompt_tokens_details\":{\"cached_tokens\":128},\"completion_tokens_details\":{\"reasoning_tokens\":12}}}\n\n",
+        "data: [DONE]\n\n",
+    );
+
+    fn create_test_stream(content: &'static str) -> impl Stream<Item = Result<Bytes, Infallible>> {
+        futures::stream::iter([Ok(Bytes::from_static(content.as_bytes()))])
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_deltas() {
+        let chunks = ChatCompletionsStream::new(create_test_stream(TOOL_CALL_STREAM))
+            .map(|chunk| chunk.unwrap().convert_to_string())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(chunks.len(), 8);
+
+        let first = &chunks[0].choices[0];
+        assert_eq!(first.delta.role, Some(ChatRole::Assistant));
+        assert_eq!(first.delta.content.as_deref(), Some(""));
+        assert_eq!(chunks[0].model.as_deref(), Some("Qwen/Qwen3-8B"));
+
+        let reasoning = chunks
+            .iter()
+            .flat_map(|chunk| &chunk.choices)
+            .filter_map(|choice| choice.delta.reasoning_text())
+            .cloned()
+            .collect::<String>();
+        assert_eq!(reasoning, "Need the weather.");
+
+        let call = &chunks[3].choices[0].delta.tool_calls[0];
+        assert_eq!(call.id.as_deref(), Some("call_a"));
+        let function = call.function.as_ref().unwrap();
+        assert_eq!(function.name.as_deref(), Some("get_current_weather"));
+
+        let arguments = chunks
+            .iter()
+            .flat_map(|chunk| &chunk.choices)
+            .flat_map(|choice| &choice.delta.tool_calls)
+            .filter(|call| call.index == 0)
+            .filter_map(|call| call.function.as_ref()?.arguments.clone())
+            .collect::<String>();
+        assert_eq!(arguments, r#"{"location": "Tokyo, Japan"}"#);
+
+        let last = &chunks[6].choices[0];
+        assert_eq!(last.finish_reason, Some(FinishReason::ToolCalls));
+        assert!(last.delta.tool_calls.is_empty());
+
+        assert!(chunks[7].choices.is_empty());
+        let usage = chunks[7].usage.as_ref().unwrap();
+        assert_eq!(
+            (
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                usage.total_tokens
+            ),
+            (152, 37, 189)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_errors_end_the_stream() {
+        let body = concat!(
+            "data: {\"id\":\"gen-1\",\"object\":\"chat.completion.chunk\",\"created\":1769599723,\"model\":\"openai/gpt-5.2\",\"provider\":\"OpenAI\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"Hel\",\"reasoning\":null},\"finish_reason\":null}]}\n\n",
+            "data: {\"id\":\"gen-1\",\"object\":\"chat.completion.chunk\",\"created\":1769599723,\"error\":{\"code\":502,\"message\":\"Provider disconnected\"},\"choices\":[{\"index\":0,\"delta\":{\"content\":\"\"},\"finish_reason\":\"error\"}]}\n\n",
+            "data: {\"id\":\"gen-1\",\"object\":\"chat.completion.chunk\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"lo\"},\"finish_reason\":null}]}\n\n",
+        );
+        let mut stream = ChatCompletionsStream::new(create_test_stream(body));
+        assert!(stream.next().await.unwrap().is_ok());
+        let Err(ChatCompletionsStreamError::Api(error)) = stream.next().await.unwrap() else {
+            panic!("expected an api error");
+        };
+        assert_eq!(error.message, "Provider disconnected");
+        assert!(stream.next().await.is_none());
+
+        let body = "data: {\"error\":{\"message\":\"model not found\",\"code\":404}}\n\n";
+        let mut stream = ChatCompletionsStream::new(create_test_stream(body));
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(ChatCompletionsStreamError::Api(_)))
+        ));
+        assert!(stream.next().await.is_none());
+    }
+}
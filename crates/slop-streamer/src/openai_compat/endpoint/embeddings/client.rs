@@ -0,0 +1,216 @@
+//! A client for the embeddings endpoint, sending large inputs in batches
+
+use serde::Serialize;
+use url::Url;
+
+use crate::{
+    openai_compat::endpoint::{
+        embeddings::{
+            OPENROUTER_EMBEDDINGS_URL,
+            request::{EmbeddingInputCollection, Request},
+            response::{EmbeddingUsage, EmbeddingsResponse},
+        },
+        responses::client::{ResponsesClientError, endpoint_url, post_json},
+    },
+    transport::HttpTransport,
+};
+
+/// The error type of [`EmbeddingsClient::embed`], a failed batch comes back as whichever of these stopped it
+pub type EmbeddingsClientError<E> = ResponsesClientError<E>;
+
+/// OpenAI's limit on inputs per request
+pub const DEFAULT_BATCH_SIZE: usize = 2048;
+
+#[derive(Debug, Clone)]
+pub struct EmbeddingsClient<H> {
+    transport: H,
+    url: Url,
+    api_key: String,
+    batch_size: usize,
+}
+
+impl<H> EmbeddingsClient<H>
+where
+    H: HttpTransport,
+{
+    /// `base_url` is the API root, e.g. `https://openrouter.ai/api/v1`, requests go to `{base_url}/embeddings`
+    pub fn new(transport: H, base_url: &Url, api_key: impl Into<String>) -> Self {
+        Self {
+            transport,
+            url: endpoint_url(base_url, "embeddings"),
+            api_key: api_key.into(),
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    pub fn openrouter(transport: H, api_key: impl Into<String>) -> Self {
+        Self {
+            transport,
+            url: OPENROUTER_EMBEDDINGS_URL.clone(),
+            api_key: api_key.into(),
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// The most inputs sent in one request, defaults to [`DEFAULT_BATCH_SIZE`]. Local servers often want far fewer.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn transport(&self) -> &H {
+        &self.transport
+    }
+
+    /// The endpoint requests are sent to
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Embed every input, one request per batch sent one after the other.
+    /// The result has the embeddings in input order with their `index` into the whole input and usage summed over the batches,
+    /// the first error stops the rest from being sent.
+    pub async fn embed<I, M>(
+        &self,
+        request: &Request<I, M>,
+    ) -> Result<EmbeddingsResponse, EmbeddingsClientError<H::Error>>
+    where
+        I: EmbeddingInputCollection,
+        M: AsRef<str>,
+    {
+        let mut combined = EmbeddingsResponse {
+            data: Vec::with_capacity(request.input.len()),
+            model: None,
+            usage: None,
+        };
+
+        for batch in request.batches(self.batch_size) {
+            let offset = batch.input.range.start as u32;
+            let response = self.send(&batch).await?;
+
+            combined
+                .data
+                .extend(response.data.into_iter().map(|mut embedding| {
+                    embedding.index += offset;
+                    embedding
+                }));
+            combined.model = combined.model.or(response.model);
+            if let Some(usage) = &response.usage {
+                *combined.usage.get_or_insert_with(EmbeddingUsage::default) += usage;
+            }
+        }
+
+        combined.data.sort_by_key(|embedding| embedding.index);
+        Ok(combined)
+    }
+
+    async fn send<R>(
+        &self,
+        request: &R,
+    ) -> Result<EmbeddingsResponse, EmbeddingsClientError<H::Error>>
+    where
+        R: Serialize,
+    {
+        post_json(
+            &self.transport,
+            self.url.clone(),
+            &self.api_key,
+            request,
+            EmbeddingsResponse::from_body,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "miri"))] // These tests don't work with miri
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+    use crate::transport::{CannedResponse, InMemoryTransport};
+
+    fn json(body: String) -> CannedResponse {
+        CannedResponse::new(StatusCode::OK, "application/json", body)
+    }
+
+    #[tokio::test]
+    async fn test_batches_keep_order_and_sum_usage() {
+        // Each batch comes back in reverse to check the results are put back in order
+        let transport = InMemoryTransport::new()
+            .with_response(json(
+                r#"{"data":[{"index":1,"embedding":[1.0]},{"index":0,"embedding":[0.0]}],"model":"m","usage":{"prompt_tokens":2,"total_tokens":2,"cost":0.5}}"#.to_string(),
+            ))
+            .with_response(json(
+                r#"{"data":[{"index":1,"embedding":[3.0]},{"index":0,"embedding":[2.0]}],"model":"m","usage":{"prompt_tokens":3,"total_tokens":3,"cost":0.25}}"#.to_string(),
+            ))
+            .with_response(json(
+                r#"{"data":[{"index":0,"embedding":[4.0]}],"usage":{"prompt_tokens":1,"total_tokens":1}}"#.to_string(),
+            ));
+        let client = EmbeddingsClient::new(
+            &transport,
+            &"http://localhost:8080/v1/".parse().unwrap(),
+            "key",
+        )
+        .with_batch_size(2);
+        assert_eq!(client.url().as_str(), "http://localhost:8080/v1/embeddings");
+
+        let texts = vec!["zero", "one", "two", "three", "four"];
+        let response = client.embed(&Request::new(&texts, "m")).await.unwrap();
+
+        let embeddings = response
+            .data
+            .iter()
+            .map(|embedding| (embedding.index, embedding.embedding[0]))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            embeddings,
+            [(0, 0.0), (1, 1.0), (2, 2.0), (3, 3.0), (4, 4.0)]
+        );
+        assert_eq!(
+            response.usage,
+            Some(EmbeddingUsage {
+                prompt_tokens: 6,
+                total_tokens: 6,
+                cost: Some(0.75),
+            })
+        );
+
+        let inputs = transport
+            .requests()
+            .iter()
+            .map(|sent| {
+                serde_json::from_slice::<serde_json::Value>(&sent.body).unwrap()["input"].clone()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            inputs,
+            [
+                serde_json::json!(["zero", "one"]),
+                serde_json::json!(["two", "three"]),
+                serde_json::json!(["four"]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_stops_batching() {
+        let transport = InMemoryTransport::new().with_response(CannedResponse::new(
+            StatusCode::BAD_REQUEST,
+            "application/json",
+            r#"{"error":{"message":"input too long","code":400}}"#,
+        ));
+        let client = EmbeddingsClient::openrouter(&transport, "key").with_batch_size(1);
+
+        let err = client
+            .embed(&Request::new(["a", "b"], "m"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ResponsesClientError::Api { status: 400, .. }));
+        assert_eq!(transport.requests().len(), 1);
+    }
+}
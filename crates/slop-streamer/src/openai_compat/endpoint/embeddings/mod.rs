@@ -0,0 +1,14 @@
+//! Module for the OAI Compatible embeddings endpoint
+
+use std::sync::LazyLock;
+
+use url::Url;
+
+pub const OPENROUTER_EMBEDDINGS_ENDPOINT: &str = "https://openrouter.ai/api/v1/embeddings";
+pub static OPENROUTER_EMBEDDINGS_URL: LazyLock<Url> =
+    LazyLock::new(|| OPENROUTER_EMBEDDINGS_ENDPOINT.parse().unwrap());
+
+#[cfg(feature = "client")]
+pub mod client;
+pub mod request;
+pub mod response;
@@ -0,0 +1,229 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeSeq};
+
+use crate::openai_compat::endpoint::responses::request::serialize_as_ref_str;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Request<I, M> {
+    #[serde(
+        bound(serialize = "I: EmbeddingInputCollection"),
+        serialize_with = "EmbeddingInputCollection::serialize_inputs"
+    )]
+    pub input: I,
+    #[serde(
+        bound(serialize = "M: AsRef<str>"),
+        serialize_with = "serialize_as_ref_str"
+    )]
+    pub model: M,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding_format: Option<EncodingFormat>,
+    /// Only supported by models trained to be truncated, e.g. `text-embedding-3-*`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+impl<I, M> Request<I, M> {
+    /// `encoding_format` is left unset, which servers treat as `float`
+    pub fn new(input: I, model: M) -> Self {
+        Self {
+            input,
+            model,
+            encoding_format: None,
+            dimensions: None,
+            user: None,
+        }
+    }
+
+    pub fn with_encoding_format(mut self, encoding_format: EncodingFormat) -> Self {
+        self.encoding_format = Some(encoding_format);
+        self
+    }
+
+    pub fn with_dimensions(mut self, dimensions: u32) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    pub fn with_user(mut self, user: impl Into<String>) -> Self {
+        self.user = Some(user.into());
+        self
+    }
+}
+
+impl<I, M> Request<I, M>
+where
+    I: EmbeddingInputCollection,
+{
+    /// The same request split into requests of at most `batch_size` inputs each, borrowing the inputs rather than copying them
+    pub fn batches(
+        &self,
+        batch_size: usize,
+    ) -> impl Iterator<Item = Request<InputRange<'_, I>, &M>> {
+        let batch_size = batch_size.max(1);
+        (0..self.input.len())
+            .step_by(batch_size)
+            .map(move |start| Request {
+                input: InputRange {
+                    inputs: &self.input,
+                    range: start..(start + batch_size).min(self.input.len()),
+                },
+                model: &self.model,
+                encoding_format: self.encoding_format,
+                dimensions: self.dimensions,
+                user: self.user.clone(),
+            })
+    }
+}
+
+/// `base64` is the little endian bytes of the `f32`s, about a quarter the size of the JSON numbers.
+/// Either way the embeddings are parsed into `Vec<f32>`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum EncodingFormat {
+    Float,
+    Base64,
+}
+
+impl std::fmt::Display for EncodingFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodingFormat::Float => "float",
+            EncodingFormat::Base64 => "base64",
+        }
+        .fmt(f)
+    }
+}
+
+/// The texts to embed, indexable so a request can be split into batches without copying any of them
+pub trait EmbeddingInputCollection {
+    fn len(&self) -> usize;
+
+    /// Panics if `idx` is out of bounds
+    fn text(&self, idx: usize) -> &str;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn serialize_inputs<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for idx in 0..self.len() {
+            seq.serialize_element(self.text(idx))?;
+        }
+        seq.end()
+    }
+}
+
+macro_rules! impl_embedding_input_collection {
+    ($($ty:ty),*) => {
+        $(
+            impl<T> EmbeddingInputCollection for $ty
+            where
+                T: AsRef<str>,
+            {
+                fn len(&self) -> usize {
+                    <[T]>::len(self)
+                }
+
+                fn text(&self, idx: usize) -> &str {
+                    self[idx].as_ref()
+                }
+            }
+        )*
+    };
+}
+
+impl_embedding_input_collection!(
+    Vec<T>,
+    &[T],
+    Box<[T]>,
+    std::sync::Arc<[T]>,
+    std::rc::Rc<[T]>,
+    [T]
+);
+
+impl<T, const LEN: usize> EmbeddingInputCollection for [T; LEN]
+where
+    T: AsRef<str>,
+{
+    fn len(&self) -> usize {
+        LEN
+    }
+
+    fn text(&self, idx: usize) -> &str {
+        self[idx].as_ref()
+    }
+}
+
+impl<T> EmbeddingInputCollection for &T
+where
+    T: EmbeddingInputCollection,
+{
+    fn len(&self) -> usize {
+        <T as EmbeddingInputCollection>::len(self)
+    }
+
+    fn text(&self, idx: usize) -> &str {
+        <T as EmbeddingInputCollection>::text(self, idx)
+    }
+}
+
+/// A contiguous part of another collection, see [`Request::batches`]
+#[derive(Debug)]
+pub struct InputRange<'a, I: ?Sized> {
+    pub inputs: &'a I,
+    pub range: Range<usize>,
+}
+
+impl<I: ?Sized> Clone for InputRange<'_, I> {
+    fn clone(&self) -> Self {
+        Self {
+            inputs: self.inputs,
+            range: self.range.clone(),
+        }
+    }
+}
+
+impl<I> EmbeddingInputCollection for InputRange<'_, I>
+where
+    I: EmbeddingInputCollection + ?Sized,
+{
+    fn len(&self) -> usize {
+        self.range.len()
+    }
+
+    fn text(&self, idx: usize) -> &str {
+        assert!(idx < self.range.len(), "index out of bounds");
+        self.inputs.text(self.range.start + idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batches_borrow_in_order() {
+        let texts = ["a", "b", "c", "d", "e"];
+        let request = Request::new(&texts[..], "openai/text-embedding-3-small")
+            .with_encoding_format(EncodingFormat::Base64)
+            .with_dimensions(256);
+
+        let batches = request
+            .batches(2)
+            .map(|batch| serde_json::to_value(&batch).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[0]["input"], serde_json::json!(["a", "b"]));
+        assert_eq!(batches[2]["input"], serde_json::json!(["e"]));
+        assert_eq!(batches[2]["model"], "openai/text-embedding-3-small");
+        assert_eq!(batches[2]["encoding_format"], "base64");
+        assert_eq!(batches[2]["dimensions"], 256);
+        assert!(batches[2].get("user").is_none());
+
+        assert_eq!(request.batches(0).count(), 5);
+    }
+}
@@ -0,0 +1,187 @@
+//! The embeddings response body, vectors are decoded straight into `Vec<f32>` from either encoding
+
+use std::borrow::Cow;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use bytes_utils::Str;
+use serde::{
+    Deserialize, Deserializer, Serialize,
+    de::{self, SeqAccess, Visitor},
+};
+
+use crate::openai_compat::endpoint::responses::stream::stream_item::{
+    ConvertToOwned, ConvertToString, impl_conversion,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingsResponse<T = Str> {
+    /// In input order once returned by [`EmbeddingsClient::embed`](super::client::EmbeddingsClient::embed), the server may not sort them
+    pub data: Vec<Embedding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<EmbeddingUsage>,
+}
+
+impl EmbeddingsResponse {
+    pub fn from_body(body: &Str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str::<EmbeddingsResponse<Cow<'_, str>>>(body)
+            .map(|borrowed| borrowed.convert_to_owned(body))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Embedding {
+    /// Position of the input this is the embedding of
+    pub index: u32,
+    /// Accepts both the `float` array and the `base64` string
+    #[serde(deserialize_with = "deserialize_vector")]
+    pub embedding: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EmbeddingUsage {
+    pub prompt_tokens: u64,
+    pub total_tokens: u64,
+    /// OpenRouter only, in credits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+}
+
+impl std::ops::AddAssign<&EmbeddingUsage> for EmbeddingUsage {
+    fn add_assign(&mut self, rhs: &EmbeddingUsage) {
+        self.prompt_tokens += rhs.prompt_tokens;
+        self.total_tokens += rhs.total_tokens;
+        self.cost = match (self.cost, rhs.cost) {
+            (None, None) => None,
+            (lhs, rhs) => Some(lhs.unwrap_or_default() + rhs.unwrap_or_default()),
+        };
+    }
+}
+
+fn deserialize_vector<'de, D>(deserializer: D) -> Result<Vec<f32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct VectorVisitor;
+
+    impl<'de> Visitor<'de> for VectorVisitor {
+        type Value = Vec<f32>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("an array of floats or a base64 string")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Vec<f32>, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut vector = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(value) = seq.next_element::<f32>()? {
+                vector.push(value);
+            }
+            Ok(vector)
+        }
+
+        // Called with a slice of the input (or serde_json's scratch buffer), so the base64 never gets its own String
+        fn visit_str<E>(self, value: &str) -> Result<Vec<f32>, E>
+        where
+            E: de::Error,
+        {
+            decode_base64_vector(value).map_err(E::custom)
+        }
+    }
+
+    deserializer.deserialize_any(VectorVisitor)
+}
+
+/// Decodes straight into the `f32` buffer rather than into bytes that then get converted
+fn decode_base64_vector(value: &str) -> Result<Vec<f32>, Base64VectorError> {
+    let len = base64::decoded_len_estimate(value.len()).div_ceil(4);
+    let mut vector = vec![0f32; len];
+
+    // Safety: every bit pattern is a valid f32, and u8 has no alignment requirement
+    let bytes = unsafe {
+        std::slice::from_raw_parts_mut(vector.as_mut_ptr().cast::<u8>(), len * size_of::<f32>())
+    };
+    let written = STANDARD
+        .decode_slice(value, bytes)
+        .map_err(Base64VectorError::Decode)?;
+    if written % size_of::<f32>() != 0 {
+        return Err(Base64VectorError::TrailingBytes(written));
+    }
+    vector.truncate(written / size_of::<f32>());
+
+    // The wire format is little endian, this is a no-op on little endian targets
+    for value in &mut vector {
+        *value = f32::from_le_bytes(value.to_ne_bytes());
+    }
+    Ok(vector)
+}
+
+#[derive(Debug)]
+enum Base64VectorError {
+    Decode(base64::DecodeSliceError),
+    /// Decoded to this many bytes, which isn't a whole number of `f32`s
+    TrailingBytes(usize),
+}
+
+impl std::fmt::Display for Base64VectorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Base64VectorError::Decode(error) => error.fmt(f),
+            Base64VectorError::TrailingBytes(len) => write!(
+                f,
+                "base64 embedding decoded to {} bytes, not a multiple of {}",
+                len,
+                size_of::<f32>()
+            ),
+        }
+    }
+}
+
+impl_conversion!(Embedding);
+impl_conversion!(EmbeddingUsage);
+impl_conversion!(EmbeddingsResponse struct [model] [data, usage]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_both_encodings_parse() {
+        let floats = [0.25f32, -1.5, 3.0];
+        let encoded = STANDARD.encode(
+            floats
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect::<Vec<_>>(),
+        );
+        let body = format!(
+            r#"{{"object":"list","data":[{{"object":"embedding","index":1,"embedding":"{}"}},{{"object":"embedding","index":0,"embedding":[0.25,-1.5,3.0]}}],"model":"text-embedding-3-small","usage":{{"prompt_tokens":4,"total_tokens":4}}}}"#,
+            encoded
+        );
+
+        let response = EmbeddingsResponse::from_body(&Str::from(body)).unwrap();
+        assert_eq!(response.data[0].embedding, floats);
+        assert_eq!(response.data[1].embedding, floats);
+        assert_eq!(response.data[0].index, 1);
+        assert_eq!(response.model.as_deref(), Some("text-embedding-3-small"));
+
+        let body = Str::from_static(r#"{"data":[{"index":0,"embedding":"not base64!"}]}"#);
+        assert!(EmbeddingsResponse::from_body(&body).is_err());
+    }
+
+    #[test]
+    fn test_base64_rejects_partial_float() {
+        // Two whole floats and half of a third
+        let encoded = STANDARD.encode([0u8; 10]);
+        let body = format!(r#"{{"data":[{{"index":0,"embedding":"{}"}}]}}"#, encoded);
+        let error = EmbeddingsResponse::from_body(&Str::from(body)).unwrap_err();
+        assert!(
+            error.to_string().contains("decoded to 10 bytes"),
+            "{}",
+            error
+        );
+    }
+}
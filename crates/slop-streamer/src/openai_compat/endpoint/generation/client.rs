@@ -0,0 +1,282 @@
+//! A client for looking up generation stats, waiting for OpenRouter to record them if needed
+
+use std::time::{Duration, Instant, SystemTime};
+
+use http::StatusCode;
+use url::Url;
+
+use crate::{
+    openai_compat::endpoint::{
+        generation::{OPENROUTER_GENERATION_URL, stats::GenerationStats},
+        responses::{
+            client::{ResponsesClientError, endpoint_url, read_json, with_headers},
+            stream::stream_item::StreamEvent,
+        },
+    },
+    transport::{
+        HttpRequest, HttpTransport,
+        retry::{FailedAttempt, RetryPolicy, retry_after},
+    },
+};
+
+/// The error type of [`GenerationClient::get`], stats that aren't recorded yet come back as a 404 [`ResponsesClientError::Api`]
+pub type GenerationClientError<E> = ResponsesClientError<E>;
+
+#[derive(Debug, Clone)]
+pub struct GenerationClient<H> {
+    transport: H,
+    url: Url,
+    api_key: String,
+}
+
+impl<H> GenerationClient<H>
+where
+    H: HttpTransport,
+{
+    /// `base_url` is the API root, e.g. `https://openrouter.ai/api/v1`, stats are fetched from `{base_url}/generation`
+    pub fn new(transport: H, base_url: &Url, api_key: impl Into<String>) -> Self {
+        Self {
+            transport,
+            url: endpoint_url(base_url, "generation"),
+            api_key: api_key.into(),
+        }
+    }
+
+    pub fn openrouter(transport: H, api_key: impl Into<String>) -> Self {
+        Self {
+            transport,
+            url: OPENROUTER_GENERATION_URL.clone(),
+            api_key: api_key.into(),
+        }
+    }
+
+    pub fn transport(&self) -> &H {
+        &self.transport
+    }
+
+    /// The endpoint stats are fetched from, without the `id` query
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Look up the stats once. Stats are recorded shortly after the response finishes,
+    /// until then this fails with a 404, see [`poll`](Self::poll).
+    pub async fn get(&self, id: &str) -> Result<GenerationStats, GenerationClientError<H::Error>> {
+        self.fetch(id).await.map_err(|(error, _)| error)
+    }
+
+    /// Look up the stats, asking again as `policy` allows while they're not recorded yet (404) or the failure is retryable.
+    /// Recording usually takes a second or two, so a policy like `RetryPolicy::default().with_max_attempts(8)` is a better fit than the default.
+    pub async fn poll(
+        &self,
+        id: &str,
+        policy: &RetryPolicy,
+    ) -> Result<GenerationStats, GenerationPollError<H::Error>> {
+        let started = Instant::now();
+        let mut attempts = Vec::new();
+
+        loop {
+            let (error, retry_after) = match self.fetch(id).await {
+                Ok(stats) => return Ok(stats),
+                Err(failure) => failure,
+            };
+
+            if !is_not_recorded_yet(&error) && !error.is_retryable() {
+                return Err(GenerationPollError::Client(error));
+            }
+
+            let elapsed = started.elapsed();
+            attempts.push(FailedAttempt {
+                error,
+                elapsed,
+                retry_after,
+            });
+            let Some(delay) = policy.next_delay(attempts.len() as u32, retry_after, elapsed) else {
+                return Err(GenerationPollError::RetriesExhausted(attempts));
+            };
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// [`poll`](Self::poll) for the response a `response.completed` or `response.incomplete` event finished,
+    /// `None` for any other event so this can be called on every event of a stream
+    pub async fn poll_after(
+        &self,
+        event: &StreamEvent,
+        policy: &RetryPolicy,
+    ) -> Option<Result<GenerationStats, GenerationPollError<H::Error>>> {
+        let id = match event {
+            StreamEvent::ResponseCompleted(data) => &data.response.id,
+            StreamEvent::ResponseIncomplete(data) => &data.response.id,
+            _ => return None,
+        };
+        Some(self.poll(id, policy).await)
+    }
+
+    async fn fetch(
+        &self,
+        id: &str,
+    ) -> Result<GenerationStats, (GenerationClientError<H::Error>, Option<Duration>)> {
+        let mut url = self.url.clone();
+        url.query_pairs_mut().append_pair("id", id);
+        let http_request = with_headers(
+            HttpRequest::get(url),
+            Some(&self.api_key),
+            "application/json",
+        )
+        .map_err(|error| (error, None))?;
+
+        // Not `get_json`, the retry headers are needed too
+        let response = self
+            .transport
+            .send(http_request)
+            .await
+            .map_err(|error| (ResponsesClientError::Transport(error), None))?;
+        let retry_after = retry_after(response.status, &response.headers, SystemTime::now());
+        read_json(response, GenerationStats::from_body)
+            .await
+            .map_err(|error| (error, retry_after))
+    }
+}
+
+/// OpenRouter answers 404 until the generation has been recorded
+fn is_not_recorded_yet<E>(error: &GenerationClientError<E>) -> bool {
+    matches!(
+        error,
+        ResponsesClientError::Api { status, .. } | ResponsesClientError::Status { status, .. }
+            if *status == StatusCode::NOT_FOUND.as_u16()
+    )
+}
+
+/// The error type of [`GenerationClient::poll`]
+#[derive(Debug)]
+pub enum GenerationPollError<E> {
+    /// A failure asking again won't fix, e.g. a bad API key
+    Client(GenerationClientError<E>),
+    /// The stats still weren't available when the policy ran out, with every failed attempt
+    RetriesExhausted(Vec<FailedAttempt<GenerationClientError<E>>>),
+}
+
+impl<E> std::fmt::Display for GenerationPollError<E>
+where
+    E: std::fmt::Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenerationPollError::Client(error) => error.fmt(f),
+            GenerationPollError::RetriesExhausted(attempts) => match attempts.last() {
+                Some(last) => write!(
+                    f,
+                    "generation stats not available after {} attempts: {}",
+                    attempts.len(),
+                    last.error
+                ),
+                None => "generation stats not available".fmt(f),
+            },
+        }
+    }
+}
+
+impl<E> std::error::Error for GenerationPollError<E> where E: std::error::Error {}
+
+#[cfg(test)]
+#[cfg(not(feature = "miri"))] // These tests don't work with miri
+mod tests {
+    use futures::StreamExt;
+    use http::header::AUTHORIZATION;
+
+    use super::*;
+    use crate::{
+        openai_compat::endpoint::responses::{client::ResponsesClient, request::Request},
+        samples,
+        transport::{CannedResponse, InMemoryTransport},
+    };
+
+    const STATS: &str = r#"{"data":{"id":"gen-1769599723-TonbLCOA2FJQZ6QlEP2C","total_cost":0.00492975,"model":"openai/gpt-5.2","provider_name":"OpenAI","latency":1250,"generation_time":6000,"native_tokens_prompt":11,"native_tokens_completion":342}}"#;
+
+    fn not_found() -> CannedResponse {
+        CannedResponse::new(
+            StatusCode::NOT_FOUND,
+            "application/json",
+            r#"{"error":{"message":"Generation not found","code":404}}"#,
+        )
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::default()
+            .with_initial_delay(Duration::from_millis(1))
+            .with_jitter(false)
+    }
+
+    #[tokio::test]
+    async fn test_polls_after_completed_event() {
+        let transport = InMemoryTransport::new()
+            .with_response(
+                CannedResponse::sample(samples::DIR, "openai_gpt-5.2", "sample1_representative")
+                    .unwrap(),
+            )
+            .with_response(not_found())
+            .with_response(not_found())
+            .with_response(CannedResponse::new(
+                StatusCode::OK,
+                "application/json",
+                STATS,
+            ));
+        let base_url = "https://example.com/api/v1".parse().unwrap();
+        let events = ResponsesClient::new(&transport, &base_url, "key")
+            .stream(&Request::builder(["hello".to_string()], "openai/gpt-5.2").build())
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+        let client = GenerationClient::new(&transport, &base_url, "key");
+
+        let mut found = Vec::new();
+        for event in &events {
+            if let Some(stats) = client.poll_after(event.as_ref().unwrap(), &policy()).await {
+                found.push(stats.unwrap());
+            }
+        }
+        let [stats] = &found[..] else {
+            panic!("Expected stats for exactly one event");
+        };
+        assert_eq!(stats.provider_name.as_deref(), Some("OpenAI"));
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[1].method, http::Method::GET);
+        assert_eq!(
+            requests[1].url.as_str(),
+            "https://example.com/api/v1/generation?id=gen-1769599723-TonbLCOA2FJQZ6QlEP2C"
+        );
+        assert_eq!(requests[1].headers[AUTHORIZATION], "Bearer key");
+    }
+
+    #[tokio::test]
+    async fn test_poll_gives_up() {
+        let transport = InMemoryTransport::new()
+            .with_response(not_found())
+            .with_response(not_found())
+            .with_response(CannedResponse::new(
+                StatusCode::UNAUTHORIZED,
+                "application/json",
+                r#"{"error":{"message":"No auth credentials found","code":401}}"#,
+            ));
+        let client = GenerationClient::openrouter(&transport, "key");
+
+        let err = client
+            .poll("gen-1", &policy().with_max_attempts(2))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, GenerationPollError::RetriesExhausted(ref attempts) if attempts.len() == 2)
+        );
+
+        // Not worth waiting on
+        let err = client.poll("gen-1", &policy()).await.unwrap_err();
+        assert!(matches!(
+            err,
+            GenerationPollError::Client(ResponsesClientError::Api { status: 401, .. })
+        ));
+    }
+}
@@ -0,0 +1,13 @@
+//! Module for OpenRouter's generation endpoint, which has the native token counts, provider and exact cost of a finished response
+
+use std::sync::LazyLock;
+
+use url::Url;
+
+pub const OPENROUTER_GENERATION_ENDPOINT: &str = "https://openrouter.ai/api/v1/generation";
+pub static OPENROUTER_GENERATION_URL: LazyLock<Url> =
+    LazyLock::new(|| OPENROUTER_GENERATION_ENDPOINT.parse().unwrap());
+
+#[cfg(feature = "client")]
+pub mod client;
+pub mod stats;
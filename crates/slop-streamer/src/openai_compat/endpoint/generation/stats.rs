@@ -0,0 +1,160 @@
+//! What OpenRouter recorded about one generation once it has finished
+
+use std::{borrow::Cow, time::Duration};
+
+use bytes_utils::Str;
+use serde::{Deserialize, Serialize};
+
+use crate::openai_compat::endpoint::responses::stream::stream_item::{
+    ConvertToOwned, ConvertToString, impl_conversion,
+};
+
+/// `tokens_*` are counted with OpenRouter's normalized (GPT-4o) tokenizer, `native_tokens_*` with the model's own,
+/// which is what the provider bills and what `total_cost` is based on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenerationStats<T = Str> {
+    /// The response id, `gen-...`
+    pub id: T,
+    /// The id the upstream provider gave the request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream_id: Option<T>,
+    /// In credits (USD)
+    #[serde(default)]
+    pub total_cost: f64,
+    /// Only set for BYOK requests, what the provider charged the key's owner
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream_inference_cost: Option<f64>,
+    /// Subtracted from the cost for reading from the prompt cache
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_discount: Option<f64>,
+    /// ISO 8601, e.g. `2026-01-28T11:28:43.627Z`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_name: Option<T>,
+    /// The endpoint the request was sent to, e.g. `completions` or `responses`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_type: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub streamed: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancelled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_byok: Option<bool>,
+    /// Milliseconds until the first token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency: Option<u64>,
+    /// Milliseconds spent on OpenRouter's moderation before the request was sent on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub moderation_latency: Option<u64>,
+    /// Milliseconds from the first token to the last
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generation_time: Option<u64>,
+    /// Normalized to the chat completions values
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<T>,
+    /// As the provider sent it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_finish_reason: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens_prompt: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens_completion: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_tokens_prompt: Option<u64>,
+    /// Including the reasoning tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_tokens_completion: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_tokens_reasoning: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub native_tokens_cached: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_media_prompt: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_media_completion: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_search_results: Option<u64>,
+}
+
+impl GenerationStats {
+    /// Parse a `{"data": {...}}` generation body
+    pub fn from_body(body: &Str) -> Result<Self, serde_json::Error> {
+        #[derive(Deserialize)]
+        struct Data<T> {
+            data: GenerationStats<T>,
+        }
+
+        serde_json::from_str::<Data<Cow<'_, str>>>(body)
+            .map(|data| data.data.convert_to_owned(body))
+    }
+}
+
+impl<T> GenerationStats<T> {
+    /// Time to first token
+    pub fn latency(&self) -> Option<Duration> {
+        self.latency.map(Duration::from_millis)
+    }
+
+    pub fn generation_time(&self) -> Option<Duration> {
+        self.generation_time.map(Duration::from_millis)
+    }
+
+    /// From sending the request on to the last token
+    pub fn total_time(&self) -> Option<Duration> {
+        Some(self.latency()? + self.generation_time()?)
+    }
+
+    /// Completion tokens per second after the first token
+    pub fn tokens_per_second(&self) -> Option<f64> {
+        let tokens = self.native_tokens_completion.or(self.tokens_completion)?;
+        let secs = self.generation_time()?.as_secs_f64();
+        (secs > 0.0).then(|| tokens as f64 / secs)
+    }
+}
+
+impl_conversion!(GenerationStats struct [id, upstream_id, created_at, model, provider_name, api_type, origin, finish_reason, native_finish_reason] [
+    total_cost,
+    upstream_inference_cost,
+    cache_discount,
+    streamed,
+    cancelled,
+    is_byok,
+    latency,
+    moderation_latency,
+    generation_time,
+    tokens_prompt,
+    tokens_completion,
+    native_tokens_prompt,
+    native_tokens_completion,
+    native_tokens_reasoning,
+    native_tokens_cached,
+    num_media_prompt,
+    num_media_completion,
+    num_search_results
+]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_generation_body() {
+        let body = Str::from_static(
+            r#"{"data":{"id":"gen-1769599723-TonbLCOA2FJQZ6QlEP2C","upstream_id":"resp_0a1b2c","total_cost":0.00492975,"cache_discount":null,"upstream_inference_cost":null,"created_at":"2026-01-28T11:28:43.627Z","model":"openai/gpt-5.2","app_id":null,"streamed":true,"cancelled":false,"provider_name":"OpenAI","latency":1250,"moderation_latency":null,"generation_time":6000,"finish_reason":"stop","native_finish_reason":"completed","tokens_prompt":12,"tokens_completion":300,"native_tokens_prompt":11,"native_tokens_completion":342,"native_tokens_reasoning":128,"native_tokens_cached":0,"num_media_prompt":null,"num_media_completion":null,"num_search_results":null,"origin":"","usage":0.00492975,"is_byok":false,"api_type":"responses"}}"#,
+        );
+
+        let stats = GenerationStats::from_body(&body).unwrap();
+        assert_eq!(&*stats.id, "gen-1769599723-TonbLCOA2FJQZ6QlEP2C");
+        assert_eq!(stats.total_cost, 0.00492975);
+        assert_eq!(stats.provider_name.as_deref(), Some("OpenAI"));
+        assert_eq!(stats.native_tokens_reasoning, Some(128));
+        assert_eq!(stats.cache_discount, None);
+        assert_eq!(stats.total_time(), Some(Duration::from_millis(7250)));
+        assert_eq!(stats.tokens_per_second(), Some(57.0));
+    }
+}
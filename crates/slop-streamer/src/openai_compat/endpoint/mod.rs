@@ -1 +1,5 @@
+pub mod chat_completions;
+pub mod embeddings;
+pub mod generation;
+pub mod models;
 pub mod responses;
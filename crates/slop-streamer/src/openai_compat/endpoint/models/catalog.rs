@@ -0,0 +1,520 @@
+//! Typed entries of the models list, and picking among them by what they support and cost
+
+use std::{borrow::Cow, ops::Deref};
+
+use bytes_utils::Str;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::openai_compat::{
+    endpoint::responses::stream::stream_item::{ConvertToOwned, ConvertToString, impl_conversion},
+    serde_helpers::null_as_default,
+};
+
+/// Every model the endpoint listed, in the order it listed them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelCatalog {
+    pub models: Vec<ModelInfo>,
+}
+
+impl ModelCatalog {
+    /// Parse a `{"data": [...]}` models list body
+    pub fn from_body(body: &Str) -> Result<Self, serde_json::Error> {
+        #[derive(Deserialize)]
+        struct ModelList<T> {
+            data: Vec<ModelInfo<T>>,
+        }
+
+        let list = serde_json::from_str::<ModelList<Cow<'_, str>>>(body)?;
+        Ok(Self {
+            models: list.data.convert_to_owned(body),
+        })
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ModelInfo> {
+        self.models.iter().find(|model| &*model.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ModelInfo> {
+        self.models.iter()
+    }
+
+    pub fn filter<'a>(&'a self, filter: &'a ModelFilter) -> impl Iterator<Item = &'a ModelInfo> {
+        self.models.iter().filter(|model| filter.matches(*model))
+    }
+
+    /// The matching model with the lowest prompt plus completion price per token.
+    /// Models without pricing, or with a negative (variable, e.g. a router's `-1`) price, are skipped.
+    pub fn cheapest<'a>(&'a self, filter: &'a ModelFilter) -> Option<&'a ModelInfo> {
+        self.filter(filter)
+            .filter_map(|model| Some((model, model.pricing.as_ref()?)))
+            .filter(|(_, pricing)| pricing.prompt >= 0.0 && pricing.completion >= 0.0)
+            .min_by(|(_, a), (_, b)| {
+                (a.prompt + a.completion).total_cmp(&(b.prompt + b.completion))
+            })
+            .map(|(model, _)| model)
+    }
+}
+
+/// Only `id` is required, OpenRouter fills in the rest while vLLM, Ollama and friends send little more than the id
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelInfo<T = Str> {
+    pub id: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canonical_slug: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<T>,
+    /// Unix timestamp in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<T>,
+    /// Input plus output tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_length: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub architecture: Option<Architecture<T>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<Pricing>,
+    /// Limits of the provider OpenRouter routes to by default, which can be lower than the model's own
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_provider: Option<TopProvider>,
+    /// Request parameters at least one provider of the model accepts, see [`Parameter`]
+    #[serde(default = "Vec::new", deserialize_with = "null_as_default")]
+    pub supported_parameters: Vec<T>,
+}
+
+impl<T> ModelInfo<T>
+where
+    T: Deref<Target = str>,
+{
+    /// Models that don't list their parameters are assumed to support everything
+    pub fn supports(&self, parameter: Parameter) -> bool {
+        self.supported_parameters.is_empty()
+            || self
+                .supported_parameters
+                .iter()
+                .any(|supported| &**supported == parameter.as_str())
+    }
+
+    pub fn supports_tools(&self) -> bool {
+        self.supports(Parameter::Tools)
+    }
+
+    pub fn supports_reasoning(&self) -> bool {
+        self.supports(Parameter::Reasoning)
+    }
+
+    pub fn supports_structured_outputs(&self) -> bool {
+        self.supports(Parameter::StructuredOutputs)
+    }
+
+    /// Whether the model takes e.g. `"image"` or `"file"` as input, `false` if the architecture wasn't given
+    pub fn accepts_input(&self, modality: &str) -> bool {
+        self.architecture.as_ref().is_some_and(|architecture| {
+            architecture
+                .input_modalities
+                .iter()
+                .any(|input| &**input == modality)
+        })
+    }
+
+    pub fn produces_output(&self, modality: &str) -> bool {
+        self.architecture.as_ref().is_some_and(|architecture| {
+            architecture
+                .output_modalities
+                .iter()
+                .any(|output| &**output == modality)
+        })
+    }
+
+    /// The largest completion the default provider allows, if it's known
+    pub fn max_completion_tokens(&self) -> Option<u64> {
+        self.top_provider.as_ref()?.max_completion_tokens
+    }
+
+    /// `Err` with every parameter in `used` the model doesn't support, once each and in [`Parameter`] order
+    pub fn check_parameters(
+        &self,
+        used: impl IntoIterator<Item = Parameter>,
+    ) -> Result<(), UnsupportedParameters> {
+        let mut unsupported = used
+            .into_iter()
+            .filter(|parameter| !self.supports(*parameter))
+            .collect::<Vec<_>>();
+        if unsupported.is_empty() {
+            return Ok(());
+        }
+
+        unsupported.sort_unstable();
+        unsupported.dedup();
+        Err(UnsupportedParameters {
+            model: self.id.to_string(),
+            parameters: unsupported,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Architecture<T = Str> {
+    /// e.g. `text`, `image`, `file`, `audio`
+    #[serde(default = "Vec::new", deserialize_with = "null_as_default")]
+    pub input_modalities: Vec<T>,
+    #[serde(default = "Vec::new", deserialize_with = "null_as_default")]
+    pub output_modalities: Vec<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokenizer: Option<T>,
+    /// Summary of the modalities, e.g. `text+image->text`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modality: Option<T>,
+}
+
+/// In USD, sent as decimal strings. Prices not listed are 0, and routers like `openrouter/auto` list -1 since the price depends on the model picked.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Pricing {
+    /// Per input token
+    #[serde(default, deserialize_with = "deserialize_price")]
+    pub prompt: f64,
+    /// Per output token, including reasoning
+    #[serde(default, deserialize_with = "deserialize_price")]
+    pub completion: f64,
+    /// Per request, on top of the tokens
+    #[serde(default, deserialize_with = "deserialize_price")]
+    pub request: f64,
+    /// Per input image
+    #[serde(default, deserialize_with = "deserialize_price")]
+    pub image: f64,
+    /// Per search, for the `:online` variants and the web search tool
+    #[serde(default, deserialize_with = "deserialize_price")]
+    pub web_search: f64,
+    /// Per reasoning token, when it's billed differently from the rest of the completion
+    #[serde(default, deserialize_with = "deserialize_price")]
+    pub internal_reasoning: f64,
+    /// Per input token read from the prompt cache
+    #[serde(default, deserialize_with = "deserialize_price")]
+    pub input_cache_read: f64,
+    /// Per input token written to the prompt cache
+    #[serde(default, deserialize_with = "deserialize_price")]
+    pub input_cache_write: f64,
+}
+
+impl Pricing {
+    /// The cost of a single request, leaving out images, searches and caching
+    pub fn cost(&self, input_tokens: u64, output_tokens: u64) -> f64 {
+        self.request + self.prompt * input_tokens as f64 + self.completion * output_tokens as f64
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TopProvider {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_length: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_completion_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_moderated: Option<bool>,
+}
+
+/// The names OpenRouter uses in `supported_parameters`, which are the chat completions names of the request fields
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Parameter {
+    Tools,
+    ToolChoice,
+    ParallelToolCalls,
+    Reasoning,
+    IncludeReasoning,
+    /// `json_schema` with `strict`, as opposed to only [`Parameter::ResponseFormat`] which may only guarantee valid JSON
+    StructuredOutputs,
+    ResponseFormat,
+    /// `max_output_tokens` in responses
+    MaxTokens,
+    Temperature,
+    TopP,
+    TopK,
+    MinP,
+    Stop,
+    FrequencyPenalty,
+    PresencePenalty,
+    RepetitionPenalty,
+    Seed,
+    Logprobs,
+    TopLogprobs,
+    LogitBias,
+    WebSearchOptions,
+    Verbosity,
+}
+
+impl Parameter {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Parameter::Tools => "tools",
+            Parameter::ToolChoice => "tool_choice",
+            Parameter::ParallelToolCalls => "parallel_tool_calls",
+            Parameter::Reasoning => "reasoning",
+            Parameter::IncludeReasoning => "include_reasoning",
+            Parameter::StructuredOutputs => "structured_outputs",
+            Parameter::ResponseFormat => "response_format",
+            Parameter::MaxTokens => "max_tokens",
+            Parameter::Temperature => "temperature",
+            Parameter::TopP => "top_p",
+            Parameter::TopK => "top_k",
+            Parameter::MinP => "min_p",
+            Parameter::Stop => "stop",
+            Parameter::FrequencyPenalty => "frequency_penalty",
+            Parameter::PresencePenalty => "presence_penalty",
+            Parameter::RepetitionPenalty => "repetition_penalty",
+            Parameter::Seed => "seed",
+            Parameter::Logprobs => "logprobs",
+            Parameter::TopLogprobs => "top_logprobs",
+            Parameter::LogitBias => "logit_bias",
+            Parameter::WebSearchOptions => "web_search_options",
+            Parameter::Verbosity => "verbosity",
+        }
+    }
+}
+
+impl std::fmt::Display for Parameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// What a model has to support to be picked, everything left unset matches anything
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelFilter {
+    pub min_context_length: Option<u64>,
+    pub parameters: Vec<Parameter>,
+    pub input_modalities: Vec<String>,
+    pub output_modalities: Vec<String>,
+    /// Per token, in USD
+    pub max_prompt_price: Option<f64>,
+    /// Per token, in USD
+    pub max_completion_price: Option<f64>,
+}
+
+impl ModelFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_min_context_length(mut self, min_context_length: u64) -> Self {
+        self.min_context_length = Some(min_context_length);
+        self
+    }
+
+    pub fn with_parameter(mut self, parameter: Parameter) -> Self {
+        self.parameters.push(parameter);
+        self
+    }
+
+    pub fn with_input_modality(mut self, modality: impl Into<String>) -> Self {
+        self.input_modalities.push(modality.into());
+        self
+    }
+
+    pub fn with_output_modality(mut self, modality: impl Into<String>) -> Self {
+        self.output_modalities.push(modality.into());
+        self
+    }
+
+    pub fn with_max_prompt_price(mut self, max_prompt_price: f64) -> Self {
+        self.max_prompt_price = Some(max_prompt_price);
+        self
+    }
+
+    pub fn with_max_completion_price(mut self, max_completion_price: f64) -> Self {
+        self.max_completion_price = Some(max_completion_price);
+        self
+    }
+
+    /// Models missing the information a condition needs (no context length, no pricing) don't match it
+    pub fn matches<T>(&self, model: &ModelInfo<T>) -> bool
+    where
+        T: Deref<Target = str>,
+    {
+        let within = |limit: Option<f64>, price: fn(&Pricing) -> f64| match limit {
+            None => true,
+            Some(limit) => model
+                .pricing
+                .as_ref()
+                .is_some_and(|pricing| (0.0..=limit).contains(&price(pricing))),
+        };
+
+        self.min_context_length
+            .is_none_or(|min| model.context_length.is_some_and(|length| length >= min))
+            && self.parameters.iter().all(|parameter| {
+                // Not listing parameters at all is fine for calling, but can't be chosen for a capability
+                !model.supported_parameters.is_empty() && model.supports(*parameter)
+            })
+            && self
+                .input_modalities
+                .iter()
+                .all(|modality| model.accepts_input(modality))
+            && self
+                .output_modalities
+                .iter()
+                .all(|modality| model.produces_output(modality))
+            && within(self.max_prompt_price, |pricing| pricing.prompt)
+            && within(self.max_completion_price, |pricing| pricing.completion)
+    }
+}
+
+/// A request uses parameters the model it's for doesn't support, which providers either reject or silently ignore
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedParameters {
+    pub model: String,
+    pub parameters: Vec<Parameter>,
+}
+
+impl std::fmt::Display for UnsupportedParameters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}` doesn't support ", self.model)?;
+        for (idx, parameter) in self.parameters.iter().enumerate() {
+            if idx > 0 {
+                ", ".fmt(f)?;
+            }
+            parameter.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnsupportedParameters {}
+
+/// OpenRouter sends prices as strings to keep their precision, local servers that send pricing at all use numbers
+fn deserialize_price<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Price<'a> {
+        Number(f64),
+        Text(Cow<'a, str>),
+    }
+
+    match Option::<Price>::deserialize(deserializer)? {
+        None => Ok(0.0),
+        Some(Price::Number(price)) => Ok(price),
+        Some(Price::Text(price)) => price.trim().parse().map_err(serde::de::Error::custom),
+    }
+}
+
+impl_conversion!(Pricing);
+impl_conversion!(TopProvider);
+
+impl_conversion!(ModelInfo struct [id, canonical_slug, name, description, architecture, supported_parameters] [created, context_length, pricing, top_provider]);
+impl_conversion!(Architecture struct [input_modalities, output_modalities, tokenizer, modality] []);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Trimmed from OpenRouter's /api/v1/models, plus what vLLM sends
+    const MODELS: &str = r#"{"data":[
+        {"id":"openai/gpt-5.2","canonical_slug":"openai/gpt-5.2-20251211","name":"OpenAI: GPT-5.2","created":1765389780,"context_length":400000,
+         "architecture":{"modality":"text+image->text","input_modalities":["file","image","text"],"output_modalities":["text"],"tokenizer":"GPT","instruct_type":null},
+         "pricing":{"prompt":"0.00000175","completion":"0.000014","request":"0","image":"0","web_search":"0.01","internal_reasoning":"0","input_cache_read":"0.000000175"},
+         "top_provider":{"context_length":400000,"max_completion_tokens":128000,"is_moderated":true},"per_request_limits":null,
+         "supported_parameters":["include_reasoning","max_tokens","reasoning","response_format","seed","structured_outputs","tool_choice","tools"],"default_parameters":{}},
+        {"id":"qwen/qwen3-8b","name":"Qwen: Qwen3 8B","context_length":32768,
+         "architecture":{"modality":"text->text","input_modalities":["text"],"output_modalities":["text"],"tokenizer":"Qwen3","instruct_type":"qwen3"},
+         "pricing":{"prompt":"0.000000035","completion":"0.000000138"},
+         "top_provider":{"context_length":32768,"max_completion_tokens":null,"is_moderated":false},
+         "supported_parameters":["frequency_penalty","max_tokens","presence_penalty","reasoning","temperature","tool_choice","tools","top_p"]},
+        {"id":"openrouter/auto","context_length":2000000,"pricing":{"prompt":"-1","completion":"-1"},"supported_parameters":null},
+        {"id":"Qwen/Qwen3-8B","object":"model","created":1769599723,"owned_by":"vllm","max_model_len":32768}
+    ]}"#;
+
+    fn catalog() -> ModelCatalog {
+        ModelCatalog::from_body(&Str::from_static(MODELS)).unwrap()
+    }
+
+    #[test]
+    fn test_parses_openrouter_and_local_entries() {
+        let catalog = catalog();
+        assert_eq!(catalog.models.len(), 4);
+
+        let gpt = catalog.get("openai/gpt-5.2").unwrap();
+        assert_eq!(gpt.context_length, Some(400_000));
+        assert_eq!(gpt.max_completion_tokens(), Some(128_000));
+        let pricing = gpt.pricing.as_ref().unwrap();
+        assert_eq!(pricing.prompt, 0.00000175);
+        assert_eq!(pricing.input_cache_write, 0.0);
+        assert!((pricing.cost(1_000_000, 1_000) - 1.764).abs() < 1e-9);
+        assert!(gpt.accepts_input("image"));
+        assert!(gpt.supports_structured_outputs());
+        assert!(!gpt.supports(Parameter::Temperature));
+
+        // Nothing listed, so nothing is ruled out
+        let local = catalog.get("Qwen/Qwen3-8B").unwrap();
+        assert!(local.supports(Parameter::Temperature));
+        assert!(!local.accepts_input("text"));
+    }
+
+    #[test]
+    fn test_filter_by_capability_and_price() {
+        let catalog = catalog();
+
+        let ids = |filter: &ModelFilter| {
+            catalog
+                .filter(filter)
+                .map(|model| model.id.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let tools = ModelFilter::new().with_parameter(Parameter::Tools);
+        assert_eq!(ids(&tools), ["openai/gpt-5.2", "qwen/qwen3-8b"]);
+        assert_eq!(
+            ids(&tools.clone().with_min_context_length(100_000)),
+            ["openai/gpt-5.2"]
+        );
+        assert_eq!(
+            ids(&tools.clone().with_input_modality("image")),
+            ["openai/gpt-5.2"]
+        );
+        // The router's -1 doesn't count as free
+        assert_eq!(
+            ids(&ModelFilter::new().with_max_prompt_price(0.000001)),
+            ["qwen/qwen3-8b"]
+        );
+
+        assert_eq!(
+            catalog.cheapest(&tools).map(|model| &*model.id),
+            Some("qwen/qwen3-8b")
+        );
+        // Without a price limit the router's -1 would otherwise win
+        assert_eq!(
+            catalog
+                .cheapest(&ModelFilter::new())
+                .map(|model| &*model.id),
+            Some("qwen/qwen3-8b")
+        );
+    }
+
+    #[test]
+    fn test_check_parameters() {
+        let catalog = catalog();
+        let gpt = catalog.get("openai/gpt-5.2").unwrap();
+
+        assert!(gpt.check_parameters([Parameter::Tools]).is_ok());
+        let err = gpt
+            .check_parameters([Parameter::Temperature, Parameter::Tools, Parameter::TopP])
+            .unwrap_err();
+        assert_eq!(err.parameters, [Parameter::Temperature, Parameter::TopP]);
+        assert_eq!(
+            err.to_string(),
+            "`openai/gpt-5.2` doesn't support temperature, top_p"
+        );
+
+        let err = gpt
+            .check_parameters([
+                Parameter::TopP,
+                Parameter::Temperature,
+                Parameter::Tools,
+                Parameter::TopP,
+            ])
+            .unwrap_err();
+        assert_eq!(err.parameters, [Parameter::Temperature, Parameter::TopP]);
+    }
+}
@@ -0,0 +1,105 @@
+//! A client for listing the models an endpoint serves
+
+use url::Url;
+
+use crate::{
+    openai_compat::endpoint::{
+        models::{OPENROUTER_MODELS_URL, catalog::ModelCatalog},
+        responses::client::{ResponsesClientError, endpoint_url, get_json},
+    },
+    transport::HttpTransport,
+};
+
+/// The error type of [`ModelsClient::list`]
+pub type ModelsClientError<E> = ResponsesClientError<E>;
+
+#[derive(Debug, Clone)]
+pub struct ModelsClient<H> {
+    transport: H,
+    url: Url,
+    api_key: Option<String>,
+}
+
+impl<H> ModelsClient<H>
+where
+    H: HttpTransport,
+{
+    /// `base_url` is the API root, e.g. `https://openrouter.ai/api/v1`, the list is fetched from `{base_url}/models`
+    pub fn new(transport: H, base_url: &Url) -> Self {
+        Self {
+            transport,
+            url: endpoint_url(base_url, "models"),
+            api_key: None,
+        }
+    }
+
+    /// OpenRouter lists its models without an API key
+    pub fn openrouter(transport: H) -> Self {
+        Self {
+            transport,
+            url: OPENROUTER_MODELS_URL.clone(),
+            api_key: None,
+        }
+    }
+
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn transport(&self) -> &H {
+        &self.transport
+    }
+
+    /// The endpoint the list is fetched from
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    pub async fn list(&self) -> Result<ModelCatalog, ModelsClientError<H::Error>> {
+        get_json(
+            &self.transport,
+            self.url.clone(),
+            self.api_key.as_deref(),
+            ModelCatalog::from_body,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "miri"))] // These tests don't work with miri
+mod tests {
+    use http::{Method, StatusCode, header::AUTHORIZATION};
+
+    use super::*;
+    use crate::transport::{CannedResponse, InMemoryTransport};
+
+    #[tokio::test]
+    async fn test_list_sends_get_with_optional_key() {
+        let body = r#"{"object":"list","data":[{"id":"Qwen/Qwen3-8B","object":"model","owned_by":"vllm"}]}"#;
+        let transport = InMemoryTransport::new()
+            .with_response(CannedResponse::new(
+                StatusCode::OK,
+                "application/json",
+                body,
+            ))
+            .with_response(CannedResponse::new(
+                StatusCode::OK,
+                "application/json",
+                body,
+            ));
+
+        let client = ModelsClient::new(&transport, &"http://localhost:8000/v1".parse().unwrap());
+        assert_eq!(client.url().as_str(), "http://localhost:8000/v1/models");
+        let catalog = client.list().await.unwrap();
+        assert_eq!(&*catalog.models[0].id, "Qwen/Qwen3-8B");
+
+        client.with_api_key("key").list().await.unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(requests[0].method, Method::GET);
+        assert!(requests[0].headers.get(AUTHORIZATION).is_none());
+        assert_eq!(requests[1].headers[AUTHORIZATION], "Bearer key");
+    }
+}
@@ -0,0 +1,13 @@
+//! Module for the models endpoint, OpenRouter's version of which says what each model supports and costs
+
+use std::sync::LazyLock;
+
+use url::Url;
+
+pub const OPENROUTER_MODELS_ENDPOINT: &str = "https://openrouter.ai/api/v1/models";
+pub static OPENROUTER_MODELS_URL: LazyLock<Url> =
+    LazyLock::new(|| OPENROUTER_MODELS_ENDPOINT.parse().unwrap());
+
+pub mod catalog;
+#[cfg(feature = "client")]
+pub mod client;
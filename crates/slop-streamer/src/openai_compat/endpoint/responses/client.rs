@@ -0,0 +1,726 @@
+//! A small client for the Responses endpoint, so sending a [`Request`] doesn't need the same POST and header boilerplate in every project
+
+use std::{
+    pin::Pin,
+    str::Utf8Error,
+    time::{Instant, SystemTime},
+};
+
+use bytes::Bytes;
+use bytes_utils::Str;
+use futures::{
+    Stream, StreamExt,
+    future::{self, Either},
+    stream,
+};
+use http::{
+    HeaderValue, StatusCode,
+    header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
+};
+use serde::Serialize;
+use url::Url;
+
+use crate::{
+    openai_compat::{
+        api_error::{ApiError, ApiErrorKind},
+        endpoint::responses::{
+            OPENROUTER_RESPONSES_URL,
+            request::Request,
+            response::Response,
+            stream::{
+                OAICompatResponsesStream, OAICompatResponsesStreamError, stream_item::StreamEvent,
+            },
+        },
+    },
+    transport::{
+        HttpRequest, HttpResponse, HttpTransport, TransportError,
+        retry::{FailedAttempt, RetryPolicy, is_retryable_status, retry_after},
+    },
+};
+
+/// The stream returned by [`ResponsesClient::stream`]
+pub type ResponsesClientStream<H> = OAICompatResponsesStream<<H as HttpTransport>::Body>;
+
+#[derive(Debug, Clone)]
+pub struct ResponsesClient<H> {
+    transport: H,
+    url: Url,
+    api_key: String,
+}
+
+impl<H> ResponsesClient<H>
+where
+    H: HttpTransport,
+{
+    /// `base_url` is the API root, e.g. `https://openrouter.ai/api/v1`, requests go to `{base_url}/responses`
+    pub fn new(transport: H, base_url: &Url, api_key: impl Into<String>) -> Self {
+        Self {
+            transport,
+            url: endpoint_url(base_url, "responses"),
+            api_key: api_key.into(),
+        }
+    }
+
+    pub fn openrouter(transport: H, api_key: impl Into<String>) -> Self {
+        Self {
+            transport,
+            url: OPENROUTER_RESPONSES_URL.clone(),
+            api_key: api_key.into(),
+        }
+    }
+
+    pub fn transport(&self) -> &H {
+        &self.transport
+    }
+
+    /// The endpoint requests are sent to
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Send `request` with `stream: true` (whatever it was set to) and parse the events as they arrive
+    pub async fn stream<I, M, T>(
+        &self,
+        request: &Request<I, M, T>,
+    ) -> Result<ResponsesClientStream<H>, ResponsesClientError<H::Error>>
+    where
+        Request<I, M, T>: Serialize,
+    {
+        let response = self.send(request, true).await?;
+        Ok(OAICompatResponsesStream::new(response.body))
+    }
+
+    /// Send `request` with `stream: false` (whatever it was set to) and parse the whole response
+    pub async fn create<I, M, T>(
+        &self,
+        request: &Request<I, M, T>,
+    ) -> Result<Response, ResponsesClientError<H::Error>>
+    where
+        Request<I, M, T>: Serialize,
+    {
+        let response = self.send(request, false).await?;
+        read_json(response, Response::from_body).await
+    }
+
+    /// Like [`stream`](Self::stream), but failures that happen before the first event (see [`ResponsesClientError::is_retryable`])
+    /// send the request again as `policy` allows. Once an event has been yielded errors are passed through as is,
+    /// since the caller may already have acted on the partial response.
+    ///
+    /// Giving up yields [`OAICompatResponsesStreamError::RetriesExhausted`], any other error before the first event is yielded as
+    /// [`OAICompatResponsesStreamError::Transport`]
+    pub fn stream_with_retry<'a, I, M, T>(
+        &'a self,
+        request: &Request<I, M, T>,
+        policy: RetryPolicy,
+    ) -> impl Stream<Item = Result<StreamEvent, RetryingStreamError<H::Error>>> + use<'a, H, I, M, T>
+    where
+        Request<I, M, T>: Serialize,
+    {
+        let request = match self.http_request(request, true) {
+            Ok(request) => request,
+            Err(error) => {
+                return Either::Left(stream::once(future::ready(Err(
+                    OAICompatResponsesStreamError::Transport(error),
+                ))));
+            }
+        };
+        let retry = Retry {
+            client: self,
+            request,
+            policy,
+            started: Instant::now(),
+            attempts: Vec::new(),
+            state: RetryState::Connecting,
+        };
+
+        Either::Right(stream::unfold(retry, |mut retry| async move {
+            let item = retry.next().await?;
+            Some((item, retry))
+        }))
+    }
+
+    fn http_request<R>(
+        &self,
+        request: &R,
+        stream: bool,
+    ) -> Result<HttpRequest, ResponsesClientError<H::Error>>
+    where
+        R: Serialize,
+    {
+        let mut body = serde_json::to_value(request).map_err(ResponsesClientError::Serialize)?;
+        body["stream"] = stream.into();
+        let body = serde_json::to_vec(&body).map_err(ResponsesClientError::Serialize)?;
+
+        let accept = if stream {
+            "text/event-stream"
+        } else {
+            "application/json"
+        };
+        let mut http_request = with_headers(
+            HttpRequest::post(self.url.clone(), body),
+            Some(&self.api_key),
+            accept,
+        )?;
+        http_request
+            .headers
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        Ok(http_request)
+    }
+
+    async fn send<R>(
+        &self,
+        request: &R,
+        stream: bool,
+    ) -> Result<HttpResponse<H::Body>, ResponsesClientError<H::Error>>
+    where
+        R: Serialize,
+    {
+        let http_request = self.http_request(request, stream)?;
+        let response = self
+            .transport
+            .send(http_request)
+            .await
+            .map_err(ResponsesClientError::Transport)?;
+        error_for_status(response).await
+    }
+}
+
+/// `{base_url}/{segment}`, whether or not `base_url` ends in a slash
+pub(crate) fn endpoint_url(base_url: &Url, segment: &str) -> Url {
+    let mut url = base_url.clone();
+    if let Ok(mut segments) = url.path_segments_mut() {
+        segments.pop_if_empty().push(segment);
+    }
+    url
+}
+
+/// Adds the `Authorization: Bearer` header when there's a key, and `Accept`
+pub(crate) fn with_headers<E>(
+    mut request: HttpRequest,
+    api_key: Option<&str>,
+    accept: &'static str,
+) -> Result<HttpRequest, ResponsesClientError<E>> {
+    if let Some(api_key) = api_key {
+        let authorization = HeaderValue::try_from(format!("Bearer {}", api_key))
+            .map_err(|_| ResponsesClientError::InvalidApiKey)?;
+        request.headers.insert(AUTHORIZATION, authorization);
+    }
+    request
+        .headers
+        .insert(ACCEPT, HeaderValue::from_static(accept));
+    Ok(request)
+}
+
+/// GET `url` from one of the endpoints that answer with plain JSON, parsing the body with `from_body`
+pub(crate) async fn get_json<H, T>(
+    transport: &H,
+    url: Url,
+    api_key: Option<&str>,
+    from_body: impl FnOnce(&Str) -> Result<T, serde_json::Error>,
+) -> Result<T, ResponsesClientError<H::Error>>
+where
+    H: HttpTransport,
+{
+    let request = with_headers(HttpRequest::get(url), api_key, "application/json")?;
+    let response = transport
+        .send(request)
+        .await
+        .map_err(ResponsesClientError::Transport)?;
+    read_json(response, from_body).await
+}
+
+/// POST `body` as JSON to one of the endpoints that answer with plain JSON, parsing the response with `from_body`
+pub(crate) async fn post_json<H, R, T>(
+    transport: &H,
+    url: Url,
+    api_key: &str,
+    body: &R,
+    from_body: impl FnOnce(&Str) -> Result<T, serde_json::Error>,
+) -> Result<T, ResponsesClientError<H::Error>>
+where
+    H: HttpTransport,
+    R: Serialize,
+{
+    let body = serde_json::to_vec(body).map_err(ResponsesClientError::Serialize)?;
+    let mut request = with_headers(
+        HttpRequest::post(url, body),
+        Some(api_key),
+        "application/json",
+    )?;
+    request
+        .headers
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    let response = transport
+        .send(request)
+        .await
+        .map_err(ResponsesClientError::Transport)?;
+    read_json(response, from_body).await
+}
+
+/// [`error_for_status`], then read the whole body and parse it with `from_body`
+pub(crate) async fn read_json<B, E, T>(
+    response: HttpResponse<B>,
+    from_body: impl FnOnce(&Str) -> Result<T, serde_json::Error>,
+) -> Result<T, ResponsesClientError<E>>
+where
+    B: Stream<Item = Result<Bytes, E>>,
+{
+    let bytes = error_for_status(response)
+        .await?
+        .collect()
+        .await
+        .map_err(ResponsesClientError::Transport)?;
+    let body =
+        Str::from_inner(bytes).map_err(|err| ResponsesClientError::Utf8(err.utf8_error()))?;
+    from_body(&body).map_err(ResponsesClientError::Deserialize)
+}
+
+pub(crate) async fn error_for_status<B, E>(
+    response: HttpResponse<B>,
+) -> Result<HttpResponse<B>, ResponsesClientError<E>>
+where
+    B: Stream<Item = Result<Bytes, E>>,
+{
+    if response.status.is_success() {
+        return Ok(response);
+    }
+
+    // Error bodies are plain JSON (or HTML from a proxy), never SSE
+    let status = response.status.as_u16();
+    let body = response
+        .collect()
+        .await
+        .map_err(ResponsesClientError::Transport)?;
+    Err(match ApiError::from_body(&body) {
+        Some(error) => ResponsesClientError::Api { status, error },
+        None => ResponsesClientError::Status {
+            status,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        },
+    })
+}
+
+/// The error type of [`ResponsesClient::stream_with_retry`]
+pub type RetryingStreamError<E> = OAICompatResponsesStreamError<ResponsesClientError<E>>;
+
+struct Retry<'a, H>
+where
+    H: HttpTransport,
+{
+    client: &'a ResponsesClient<H>,
+    request: HttpRequest,
+    policy: RetryPolicy,
+    started: Instant,
+    attempts: Vec<FailedAttempt<ResponsesClientError<H::Error>>>,
+    state: RetryState<H>,
+}
+
+enum RetryState<H>
+where
+    H: HttpTransport,
+{
+    Connecting,
+    Streaming {
+        events: Pin<Box<OAICompatResponsesStream<H::Body>>>,
+        /// Whether anything has been handed to the caller, after which it's too late to retry
+        yielded: bool,
+    },
+    Done,
+}
+
+impl<H> Retry<'_, H>
+where
+    H: HttpTransport,
+{
+    async fn next(&mut self) -> Option<Result<StreamEvent, RetryingStreamError<H::Error>>> {
+        loop {
+            let (error, retry_after) = match std::mem::replace(&mut self.state, RetryState::Done) {
+                RetryState::Done => return None,
+                RetryState::Connecting => {
+                    match self.client.transport.send(self.request.clone()).await {
+                        Err(error) => (ResponsesClientError::Transport(error), None),
+                        Ok(response) => {
+                            let retry_after =
+                                retry_after(response.status, &response.headers, SystemTime::now());
+                            match error_for_status(response).await {
+                                Ok(response) => {
+                                    self.state = RetryState::Streaming {
+                                        events: Box::pin(OAICompatResponsesStream::new(
+                                            response.body,
+                                        )),
+                                        yielded: false,
+                                    };
+                                    continue;
+                                }
+                                Err(error) => (error, retry_after),
+                            }
+                        }
+                    }
+                }
+                RetryState::Streaming {
+                    mut events,
+                    yielded,
+                } => match events.next().await {
+                    None => return None,
+                    Some(Ok(event)) => {
+                        self.state = RetryState::Streaming {
+                            events,
+                            yielded: true,
+                        };
+                        return Some(Ok(event));
+                    }
+                    Some(Err(OAICompatResponsesStreamError::Transport(error))) if !yielded => {
+                        (ResponsesClientError::Transport(error), None)
+                    }
+                    Some(Err(error)) => {
+                        self.state = RetryState::Streaming {
+                            events,
+                            yielded: true,
+                        };
+                        return Some(Err(error.map_transport(ResponsesClientError::Transport)));
+                    }
+                },
+            };
+
+            if !error.is_retryable() {
+                return Some(Err(OAICompatResponsesStreamError::Transport(error)));
+            }
+
+            let elapsed = self.started.elapsed();
+            self.attempts.push(FailedAttempt {
+                error,
+                elapsed,
+                retry_after,
+            });
+            let Some(delay) =
+                self.policy
+                    .next_delay(self.attempts.len() as u32, retry_after, elapsed)
+            else {
+                return Some(Err(OAICompatResponsesStreamError::RetriesExhausted(
+                    std::mem::take(&mut self.attempts),
+                )));
+            };
+            tokio::time::sleep(delay).await;
+            self.state = RetryState::Connecting;
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ResponsesClientError<E> {
+    Transport(E),
+    /// The API key can't be sent as a header value
+    InvalidApiKey,
+    /// The request couldn't be serialized
+    Serialize(serde_json::Error),
+    /// A `stream: false` response body couldn't be parsed
+    Deserialize(serde_json::Error),
+    Utf8(Utf8Error),
+    /// A non-2xx status with a `{"error": {...}}` body
+    Api {
+        status: u16,
+        error: ApiError,
+    },
+    /// A non-2xx status with any other body
+    Status {
+        status: u16,
+        body: String,
+    },
+}
+
+impl<E> ResponsesClientError<E>
+where
+    E: TransportError,
+{
+    /// Whether sending the same request again might succeed, i.e. the connection dropped (see [`TransportError::is_transient`])
+    /// or the provider is rate limiting or unavailable
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ResponsesClientError::Transport(error) => error.is_transient(),
+            ResponsesClientError::Api { status, error } => {
+                error.classify(Some(*status)).is_retryable()
+            }
+            ResponsesClientError::Status { status, .. } => {
+                StatusCode::from_u16(*status).is_ok_and(is_retryable_status)
+            }
+            ResponsesClientError::InvalidApiKey
+            | ResponsesClientError::Serialize(_)
+            | ResponsesClientError::Deserialize(_)
+            | ResponsesClientError::Utf8(_) => false,
+        }
+    }
+}
+
+impl<E> ResponsesClientError<E> {
+    /// Why the provider rejected the request, `None` if it never answered
+    pub fn kind(&self) -> Option<ApiErrorKind> {
+        match self {
+            ResponsesClientError::Api { status, error } => Some(error.classify(Some(*status))),
+            ResponsesClientError::Status { status, .. } => Some(ApiErrorKind::from_status(*status)),
+            _ => None,
+        }
+    }
+}
+
+impl<E> std::fmt::Display for ResponsesClientError<E>
+where
+    E: std::fmt::Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResponsesClientError::Transport(error) => error.fmt(f),
+            ResponsesClientError::InvalidApiKey => "api key is not a valid header value".fmt(f),
+            ResponsesClientError::Serialize(error) => {
+                write!(f, "failed to serialize request: {}", error)
+            }
+            ResponsesClientError::Deserialize(error) => {
+                write!(f, "failed to parse response: {}", error)
+            }
+            ResponsesClientError::Utf8(error) => error.fmt(f),
+            ResponsesClientError::Api { status, error } => write!(f, "{} ({})", error, status),
+            ResponsesClientError::Status { status, body } => {
+                write!(f, "unexpected status {}: {}", status, body)
+            }
+        }
+    }
+}
+
+impl<E> std::error::Error for ResponsesClientError<E> where E: std::error::Error {}
+
+#[cfg(test)]
+#[cfg(not(feature = "miri"))] // These tests don't work with miri
+mod tests {
+    use futures::StreamExt;
+    use http::StatusCode;
+
+    use super::*;
+    use crate::{
+        openai_compat::{
+            api_error::ErrorCode, endpoint::responses::stream::stream_item::StreamEvent,
+        },
+        samples,
+        transport::{CannedResponse, InMemoryTransport, in_memory::InMemoryTransportError},
+    };
+
+    fn request() -> Request<[String; 1], &'static str> {
+        Request::builder(["hello".to_string()], "openai/gpt-5.2").build()
+    }
+
+    fn client(transport: &InMemoryTransport) -> ResponsesClient<&InMemoryTransport> {
+        ResponsesClient::new(
+            transport,
+            &"https://example.com/api/v1".parse().unwrap(),
+            "test-key",
+        )
+    }
+
+    #[tokio::test]
+    async fn test_stream_sends_sse_request() {
+        let transport = InMemoryTransport::new().with_response(
+            CannedResponse::sample(samples::DIR, "openai_gpt-5.2", "sample1_representative")
+                .unwrap()
+                .with_chunk_size(64),
+        );
+        let client = client(&transport);
+        assert_eq!(client.url().path(), "/api/v1/responses");
+
+        // stream is forced on regardless of what the request says
+        let mut request = request();
+        request.stream = false;
+
+        let events = client
+            .stream(&request)
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+        assert!(matches!(
+            events.last(),
+            Some(Ok(StreamEvent::ResponseCompleted(_)))
+        ));
+
+        let [sent] = &transport.requests()[..] else {
+            panic!("Expected exactly one request");
+        };
+        assert_eq!(sent.method, http::Method::POST);
+        assert_eq!(sent.url.as_str(), "https://example.com/api/v1/responses");
+        assert_eq!(sent.headers[AUTHORIZATION], "Bearer test-key");
+        assert_eq!(sent.headers[ACCEPT], "text/event-stream");
+        let body: serde_json::Value = serde_json::from_slice(&sent.body).unwrap();
+        assert_eq!(body["stream"], true);
+    }
+
+    #[tokio::test]
+    async fn test_create_parses_response() {
+        let body = r#"{"id":"resp_1","object":"response","status":"completed","model":"openai/gpt-5.2","output":[{"type":"message","id":"msg_1","status":"completed","role":"assistant","content":[{"type":"output_text","text":"Hi!","annotations":[]}]}],"usage":{"input_tokens":8,"output_tokens":3,"total_tokens":11}}"#;
+        let transport = InMemoryTransport::new().with_response(CannedResponse::new(
+            StatusCode::OK,
+            "application/json",
+            body,
+        ));
+
+        let response = client(&transport).create(&request()).await.unwrap();
+        assert_eq!(&*response.id, "resp_1");
+        assert_eq!(response.output.len(), 1);
+        assert_eq!(response.usage.unwrap().total_tokens, 11);
+
+        let sent = &transport.requests()[0];
+        assert_eq!(sent.headers[ACCEPT], "application/json");
+        let body: serde_json::Value = serde_json::from_slice(&sent.body).unwrap();
+        assert_eq!(body["stream"], false);
+    }
+
+    #[tokio::test]
+    async fn test_error_status_is_typed() {
+        let transport = InMemoryTransport::new()
+            .with_response(CannedResponse::new(
+                StatusCode::UNAUTHORIZED,
+                "application/json",
+                r#"{"error":{"code":401,"message":"No auth credentials found"}}"#,
+            ))
+            .with_response(CannedResponse::new(
+                StatusCode::BAD_GATEWAY,
+                "text/html",
+                "Bad Gateway",
+            ));
+        let client = client(&transport);
+
+        let Err(err) = client.stream(&request()).await else {
+            panic!("Expected a 401 to be an error");
+        };
+        match err {
+            ResponsesClientError::Api { status, error } => {
+                assert_eq!(status, 401);
+                assert_eq!(error.code, Some(ErrorCode::Number(401)));
+                assert_eq!(error.message, "No auth credentials found");
+            }
+            other => panic!("Expected api error, got {:?}", other),
+        }
+
+        let err = client.create(&request()).await.unwrap_err();
+        assert!(matches!(
+            err,
+            ResponsesClientError::Status { status: 502, ref body } if body == "Bad Gateway"
+        ));
+
+        // Nothing left to answer with
+        let err = client.create(&request()).await.unwrap_err();
+        assert!(matches!(err, ResponsesClientError::Transport(_)));
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::default()
+            .with_initial_delay(std::time::Duration::from_millis(1))
+            .with_jitter(false)
+    }
+
+    fn sample() -> CannedResponse {
+        CannedResponse::sample(samples::DIR, "openai_gpt-5.2", "sample1_representative").unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_retries_before_first_event() {
+        let transport = InMemoryTransport::new()
+            .with_response(
+                CannedResponse::new(StatusCode::SERVICE_UNAVAILABLE, "text/plain", "busy")
+                    .with_header("retry-after", "0"),
+            )
+            // Dies partway through the first event
+            .with_response(sample().with_failure_after(20))
+            .with_response(sample());
+        let client = client(&transport);
+
+        let events = client
+            .stream_with_retry(&request(), policy())
+            .collect::<Vec<_>>()
+            .await;
+        assert!(events.iter().all(Result::is_ok));
+        assert!(matches!(
+            events.last(),
+            Some(Ok(StreamEvent::ResponseCompleted(_)))
+        ));
+        assert_eq!(transport.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_after_first_event() {
+        let transport = InMemoryTransport::new()
+            .with_response(sample().with_failure_after(2000))
+            .with_response(sample());
+        let client = client(&transport);
+
+        let events = client
+            .stream_with_retry(&request(), policy())
+            .collect::<Vec<_>>()
+            .await;
+        assert!(matches!(events[0], Ok(StreamEvent::ResponseCreated(_))));
+        assert!(matches!(
+            events.last(),
+            Some(Err(OAICompatResponsesStreamError::Transport(
+                ResponsesClientError::Transport(InMemoryTransportError::ConnectionReset)
+            )))
+        ));
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_on_permanent_transport_error() {
+        let transport = InMemoryTransport::new();
+        let events = client(&transport)
+            .stream_with_retry(&request(), policy())
+            .collect::<Vec<_>>()
+            .await;
+        assert!(matches!(
+            &events[..],
+            [Err(OAICompatResponsesStreamError::Transport(
+                ResponsesClientError::Transport(InMemoryTransportError::NoResponse { .. })
+            ))]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_with_history() {
+        let rate_limited = || {
+            CannedResponse::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                "application/json",
+                r#"{"error":{"code":429,"message":"Rate limit exceeded"}}"#,
+            )
+            .with_header("retry-after-ms", "5")
+        };
+        let transport = InMemoryTransport::new()
+            .with_response(rate_limited())
+            .with_response(rate_limited())
+            .with_response(sample());
+        let events = client(&transport)
+            .stream_with_retry(&request(), policy().with_max_attempts(2))
+            .collect::<Vec<_>>()
+            .await;
+        let [Err(OAICompatResponsesStreamError::RetriesExhausted(attempts))] = &events[..] else {
+            panic!("Expected to give up, got {:?}", events);
+        };
+        assert_eq!(attempts.len(), 2);
+        assert!(attempts.iter().all(|attempt| {
+            attempt.retry_after == Some(std::time::Duration::from_millis(5))
+                && matches!(attempt.error, ResponsesClientError::Api { status: 429, .. })
+        }));
+        assert_eq!(transport.requests().len(), 2);
+
+        // Not worth retrying at all
+        let transport = InMemoryTransport::new().with_response(CannedResponse::new(
+            StatusCode::UNAUTHORIZED,
+            "application/json",
+            r#"{"error":{"code":401,"message":"No auth credentials found"}}"#,
+        ));
+        let events = client(&transport)
+            .stream_with_retry(&request(), policy())
+            .collect::<Vec<_>>()
+            .await;
+        assert!(matches!(
+            &events[..],
+            [Err(OAICompatResponsesStreamError::Transport(
+                ResponsesClientError::Api { status: 401, .. }
+            ))]
+        ));
+    }
+}
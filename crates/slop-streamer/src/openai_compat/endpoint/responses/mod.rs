@@ -8,6 +8,10 @@ pub const OPENROUTER_RESPONSES_ENDPOINT: &str = "https://openrouter.ai/api/v1/re
 pub static OPENROUTER_RESPONSES_URL: LazyLock<Url> =
     LazyLock::new(|| OPENROUTER_RESPONSES_ENDPOINT.parse().unwrap());
 
+#[cfg(feature = "client")]
+pub mod client;
 pub mod contract_macro;
 pub mod request;
+pub mod response;
 pub mod stream;
+pub mod usage;
@@ -0,0 +1,223 @@
+//! Builder for [`Request`], so the common case of "input, model, go" doesn't need to spell out every optional field
+
+use std::{collections::BTreeMap, ops::Deref};
+
+use schemars::JsonSchema;
+
+use crate::openai_compat::endpoint::{
+    models::catalog::{ModelInfo, Parameter, UnsupportedParameters},
+    responses::request::{
+        Include, Request, RequestOptions, ServiceTier, Truncation,
+        openrouter::{OpenRouterOptions, ProviderPreferences},
+        reasoning::{ReasoningConfig, ReasoningEffort},
+        text_format::{TextConfig, TextFormat},
+        tools::ToolCollection,
+    },
+};
+
+/// Builder for [`Request`]
+///
+/// Defaults to `stream: true` and `parallel_tool_calls: true`, everything else is left unset and skipped when serializing
+#[derive(Debug, Clone)]
+pub struct RequestBuilder<I, M, T = ()> {
+    request: Request<I, M, T>,
+}
+
+macro_rules! builder_setters {
+    ($($field:ident: $ty:ty),* $(,)?) => {
+        $(
+            pub fn $field(mut self, $field: $ty) -> Self {
+                self.request.options.$field = Some($field);
+                self
+            }
+        )*
+    };
+    (into $($field:ident: $ty:ty),* $(,)?) => {
+        $(
+            pub fn $field(mut self, $field: impl Into<$ty>) -> Self {
+                self.request.options.$field = Some($field.into());
+                self
+            }
+        )*
+    };
+}
+
+impl<I, M> RequestBuilder<I, M> {
+    pub fn new(input: I, model: M) -> Self {
+        Self {
+            request: Request {
+                input,
+                model,
+                parallel_tool_calls: true,
+                stream: true,
+                tools: (),
+                options: RequestOptions::default(),
+            },
+        }
+    }
+}
+
+impl<I, M, T> RequestBuilder<I, M, T> {
+    pub fn parallel_tool_calls(mut self, parallel_tool_calls: bool) -> Self {
+        self.request.parallel_tool_calls = parallel_tool_calls;
+        self
+    }
+
+    pub fn stream(mut self, stream: bool) -> Self {
+        self.request.stream = stream;
+        self
+    }
+
+    builder_setters!(
+        temperature: f64,
+        top_p: f64,
+        max_output_tokens: u64,
+        max_tool_calls: u64,
+        presence_penalty: f64,
+        frequency_penalty: f64,
+        top_logprobs: u32,
+        store: bool,
+        truncation: Truncation,
+        service_tier: ServiceTier,
+        reasoning: ReasoningConfig,
+    );
+
+    builder_setters!(into
+        instructions: String,
+        user: String,
+        safety_identifier: String,
+        prompt_cache_key: String,
+    );
+
+    /// Shorthand for a [`ReasoningConfig`] with only `effort`, keeping the rest of one already set
+    pub fn reasoning_effort(mut self, effort: ReasoningEffort) -> Self {
+        self.request
+            .options
+            .reasoning
+            .get_or_insert_with(ReasoningConfig::default)
+            .effort = Some(effort);
+        self
+    }
+
+    /// Add to the extra data included in the response, ignoring duplicates
+    pub fn include(mut self, include: Include) -> Self {
+        if !self.request.options.include.contains(&include) {
+            self.request.options.include.push(include);
+        }
+        self
+    }
+
+    pub fn text_format(mut self, format: TextFormat) -> Self {
+        self.request.options.text = Some(TextConfig { format });
+        self
+    }
+
+    /// Have the output match the schema of `S`, see [`TextFormat::json_schema`].
+    /// [`OutputTextDoneData::parse_json`](crate::openai_compat::endpoint::responses::stream::stream_item::OutputTextDoneData::parse_json) reads it back.
+    pub fn json_schema<S>(self) -> Self
+    where
+        S: JsonSchema,
+    {
+        self.text_format(TextFormat::json_schema::<S>())
+    }
+
+    /// Add a single metadata entry, keeping any already set
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.request
+            .options
+            .metadata
+            .get_or_insert_with(BTreeMap::new)
+            .insert(key.into(), value.into());
+        self
+    }
+
+    /// Replace the metadata map entirely
+    pub fn metadata_map(mut self, metadata: BTreeMap<String, String>) -> Self {
+        self.request.options.metadata = Some(metadata);
+        self
+    }
+
+    /// OpenRouter only, models to fall back to in order, replacing any previously set
+    pub fn fallback_models<S>(mut self, models: impl IntoIterator<Item = S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.request
+            .options
+            .openrouter
+            .get_or_insert_with(OpenRouterOptions::default)
+            .models = models.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// OpenRouter only, how to pick among the providers serving the model
+    pub fn provider(mut self, provider: ProviderPreferences) -> Self {
+        self.request
+            .options
+            .openrouter
+            .get_or_insert_with(OpenRouterOptions::default)
+            .provider = Some(provider);
+        self
+    }
+
+    /// Set the tools the model may call, replacing any previously set
+    pub fn tools<U>(self, tools: U) -> RequestBuilder<I, M, U>
+    where
+        U: ToolCollection,
+    {
+        let Request {
+            input,
+            model,
+            parallel_tool_calls,
+            stream,
+            tools: _,
+            options,
+        } = self.request;
+
+        RequestBuilder {
+            request: Request {
+                input,
+                model,
+                parallel_tool_calls,
+                stream,
+                tools,
+                options,
+            },
+        }
+    }
+
+    pub fn build(self) -> Request<I, M, T> {
+        self.request
+    }
+}
+
+impl<I, M, T> RequestBuilder<I, M, T>
+where
+    T: ToolCollection,
+{
+    /// Check every parameter set so far is one `model` lists as supported, before a provider rejects or ignores it
+    pub fn validate_for<U>(&self, model: &ModelInfo<U>) -> Result<(), UnsupportedParameters>
+    where
+        U: Deref<Target = str>,
+    {
+        let request = &self.request.options;
+        let used = [
+            (!self.request.tools.is_empty()).then_some(Parameter::Tools),
+            request.temperature.map(|_| Parameter::Temperature),
+            request.top_p.map(|_| Parameter::TopP),
+            request.max_output_tokens.map(|_| Parameter::MaxTokens),
+            request.presence_penalty.map(|_| Parameter::PresencePenalty),
+            request
+                .frequency_penalty
+                .map(|_| Parameter::FrequencyPenalty),
+            request.top_logprobs.map(|_| Parameter::TopLogprobs),
+            request.reasoning.map(|_| Parameter::Reasoning),
+            request.text.as_ref().and_then(|text| match text.format {
+                TextFormat::Text => None,
+                TextFormat::JsonObject => Some(Parameter::ResponseFormat),
+                TextFormat::JsonSchema(_) => Some(Parameter::StructuredOutputs),
+            }),
+        ];
+        model.check_parameters(used.into_iter().flatten())
+    }
+}
@@ -0,0 +1,376 @@
+//! Content parts for [`InputMessage`](super::input_type::InputMessage)s that are more than a single string.
+//! Same idea as the input items, each part type is a contract you implement on your own types,
+//! so image and file bytes can stay wherever you already keep them and only get base64 encoded as they're serialized.
+
+use std::borrow::Cow;
+
+use base64::{display::Base64Display, engine::general_purpose::STANDARD};
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeSeq};
+use slop_streamer_proc_macros::contract_trait;
+
+mod private {
+    pub trait Sealed {}
+}
+
+pub trait InputContentPart: private::Sealed + erased_serde::Serialize {
+    fn as_erased(&self) -> &dyn erased_serde::Serialize
+    where
+        Self: Sized,
+    {
+        self
+    }
+}
+
+// Same as AsInputItem, lets user enums of part types be used without implementing the sealed trait themselves
+pub trait AsInputContentPart {
+    fn erase_variant(&self) -> &dyn InputContentPart;
+}
+erased_serde::serialize_trait_object!(InputContentPart);
+
+/// Object safe collection of content parts, so [`MessageContent`] can borrow one out of the message without knowing its type
+pub trait InputContentPartCollection {
+    fn len(&self) -> usize;
+
+    /// Panics if `idx` is out of bounds
+    fn part(&self, idx: usize) -> &dyn InputContentPart;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+macro_rules! impl_input_content_part_collection {
+    ($($ty:ty),*) => {
+        $(
+            impl<T> InputContentPartCollection for $ty
+            where
+                T: AsInputContentPart,
+            {
+                fn len(&self) -> usize {
+                    <[T]>::len(self)
+                }
+
+                fn part(&self, idx: usize) -> &dyn InputContentPart {
+                    self[idx].erase_variant()
+                }
+            }
+        )*
+    };
+}
+
+impl_input_content_part_collection!(
+    Vec<T>,
+    &[T],
+    Box<[T]>,
+    std::sync::Arc<[T]>,
+    std::rc::Rc<[T]>,
+    [T]
+);
+
+impl<T, const LEN: usize> InputContentPartCollection for [T; LEN]
+where
+    T: AsInputContentPart,
+{
+    fn len(&self) -> usize {
+        LEN
+    }
+
+    fn part(&self, idx: usize) -> &dyn InputContentPart {
+        self[idx].erase_variant()
+    }
+}
+
+/// The `content` of an [`InputMessage`](super::input_type::InputMessage), either plain text or a list of parts
+#[derive(Clone)]
+pub enum MessageContent<'a> {
+    Text(Cow<'a, str>),
+    Parts(&'a dyn InputContentPartCollection),
+}
+
+impl<'a> From<&'a str> for MessageContent<'a> {
+    fn from(value: &'a str) -> Self {
+        MessageContent::Text(Cow::Borrowed(value))
+    }
+}
+
+impl From<String> for MessageContent<'_> {
+    fn from(value: String) -> Self {
+        MessageContent::Text(Cow::Owned(value))
+    }
+}
+
+impl Serialize for MessageContent<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            MessageContent::Text(text) => serializer.serialize_str(text),
+            MessageContent::Parts(parts) => {
+                let mut seq = serializer.serialize_seq(Some(parts.len()))?;
+                for idx in 0..parts.len() {
+                    seq.serialize_element(parts.part(idx))?;
+                }
+                seq.end()
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for MessageContent<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MessageContent::Text(text) => f.debug_tuple("Text").field(text).finish(),
+            MessageContent::Parts(parts) => f
+                .debug_struct("Parts")
+                .field("len", &parts.len())
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+/// Bytes sent inline as a `data:` URL, encoded while serializing rather than up front
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DataUrl<'a> {
+    /// e.g. `image/png` or `application/pdf`
+    pub media_type: Cow<'a, str>,
+    pub data: Cow<'a, [u8]>,
+}
+
+impl<'a> DataUrl<'a> {
+    pub fn new(media_type: impl Into<Cow<'a, str>>, data: impl Into<Cow<'a, [u8]>>) -> Self {
+        Self {
+            media_type: media_type.into(),
+            data: data.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for DataUrl<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "data:{};base64,{}",
+            self.media_type,
+            Base64Display::new(&self.data[..], &STANDARD)
+        )
+    }
+}
+
+impl Serialize for DataUrl<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Where an [`InputImage`] comes from when it isn't an uploaded file
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ImageUrl<'a> {
+    Url(Cow<'a, str>),
+    Data(DataUrl<'a>),
+}
+
+impl Serialize for ImageUrl<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ImageUrl::Url(url) => serializer.serialize_str(url),
+            ImageUrl::Data(data) => data.serialize(serializer),
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageDetail {
+    Low,
+    High,
+    #[default]
+    Auto,
+}
+
+impl std::fmt::Display for ImageDetail {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageDetail::Low => "low",
+            ImageDetail::High => "high",
+            ImageDetail::Auto => "auto",
+        }
+        .fmt(f)
+    }
+}
+
+contract_trait!(
+    #[impl_traits(InputContentPart, AsInputContentPart)]
+    #[wrapper(Text)]
+    pub trait InputText {
+        text: Cow<'_, str>,
+        const "type" = "input_text",
+    }
+);
+
+impl InputText for str {
+    fn text(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl InputText for String {
+    fn text(&self) -> Cow<'_, str> {
+        <str as InputText>::text(self)
+    }
+}
+
+impl AsInputContentPart for String {
+    fn erase_variant(&self) -> &dyn InputContentPart {
+        self.as_wrapper_ref()
+    }
+}
+
+contract_trait!(
+    #[impl_traits(InputContentPart, AsInputContentPart)]
+    #[wrapper(Image)]
+    pub trait InputImage {
+        // Either this or `file_id` should be set
+        #[skip_serializing_if(Option::is_none)]
+        image_url: Option<ImageUrl<'_>> = None,
+        #[skip_serializing_if(Option::is_none)]
+        file_id: Option<Cow<'_, str>> = None,
+        detail: ImageDetail = ImageDetail::Auto,
+        const "type" = "input_image",
+    }
+);
+
+contract_trait!(
+    #[impl_traits(InputContentPart, AsInputContentPart)]
+    #[wrapper(File)]
+    pub trait InputFile {
+        // One of `file_data`, `file_url` or `file_id` should be set
+        #[skip_serializing_if(Option::is_none)]
+        file_data: Option<DataUrl<'_>> = None,
+        #[skip_serializing_if(Option::is_none)]
+        file_url: Option<Cow<'_, str>> = None,
+        #[skip_serializing_if(Option::is_none)]
+        file_id: Option<Cow<'_, str>> = None,
+        #[skip_serializing_if(Option::is_none)]
+        filename: Option<Cow<'_, str>> = None,
+        const "type" = "input_file",
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::openai_compat::endpoint::responses::request::input_type::{
+        AsInputItem, InputItem, InputMessage, Role,
+    };
+
+    struct Screenshot {
+        png: Vec<u8>,
+    }
+
+    impl InputImage for Screenshot {
+        fn image_url(&self) -> Option<ImageUrl<'_>> {
+            Some(ImageUrl::Data(DataUrl::new("image/png", &self.png[..])))
+        }
+
+        fn detail(&self) -> ImageDetail {
+            ImageDetail::High
+        }
+    }
+
+    struct Report<'a> {
+        name: &'a str,
+        pdf: &'a [u8],
+    }
+
+    impl InputFile for Report<'_> {
+        fn file_data(&self) -> Option<DataUrl<'_>> {
+            Some(DataUrl::new("application/pdf", self.pdf))
+        }
+
+        fn filename(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.name))
+        }
+    }
+
+    enum Part<'a> {
+        Text(&'a str),
+        Screenshot(Screenshot),
+        Report(Report<'a>),
+    }
+
+    impl AsInputContentPart for Part<'_> {
+        fn erase_variant(&self) -> &dyn InputContentPart {
+            match self {
+                Part::Text(text) => InputText::as_wrapper_ref(text),
+                Part::Screenshot(screenshot) => screenshot.as_wrapper_ref(),
+                Part::Report(report) => report.as_wrapper_ref(),
+            }
+        }
+    }
+
+    struct Question<'a> {
+        parts: Vec<Part<'a>>,
+    }
+
+    impl InputMessage for Question<'_> {
+        fn content(&self) -> MessageContent<'_> {
+            MessageContent::Parts(&self.parts)
+        }
+
+        fn role(&self) -> Role {
+            Role::User
+        }
+    }
+
+    impl AsInputItem for Question<'_> {
+        fn erase_variant(&self) -> &dyn InputItem {
+            self.as_wrapper_ref()
+        }
+    }
+
+    #[test]
+    fn test_message_with_parts() {
+        let question = Question {
+            parts: vec![
+                Part::Text("what's in these?"),
+                Part::Screenshot(Screenshot {
+                    png: vec![0x89, b'P', b'N', b'G'],
+                }),
+                Part::Report(Report {
+                    name: "report.pdf",
+                    pdf: b"%PDF-1.7",
+                }),
+            ],
+        };
+
+        let json = serde_json::to_value(question.erase_variant()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "role": "user",
+                "content": [
+                    {"type": "input_text", "text": "what's in these?"},
+                    {"type": "input_image", "image_url": "data:image/png;base64,iVBORw==", "detail": "high"},
+                    {"type": "input_file", "file_data": "data:application/pdf;base64,JVBERi0xLjc=", "filename": "report.pdf"},
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_image_by_file_id() {
+        struct Uploaded;
+
+        impl InputImage for Uploaded {
+            fn file_id(&self) -> Option<Cow<'_, str>> {
+                Some(Cow::Borrowed("file-abc123"))
+            }
+        }
+
+        let json = serde_json::to_string(Uploaded.as_wrapper_ref()).unwrap();
+        assert_eq!(
+            json,
+            r#"{"file_id":"file-abc123","detail":"auto","type":"input_image"}"#
+        );
+    }
+}
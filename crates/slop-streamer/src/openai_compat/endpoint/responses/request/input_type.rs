@@ -2,10 +2,19 @@
 //! This is an experiment in 'trait based' API writing instead of type based.
 //! You bring your own types (meaning you're free to use references and slices or whatever) and implement only the request items you actually use
 
-use std::borrow::Cow;
+use std::{borrow::Cow, ops::Deref};
 
 use serde::{Deserialize, Serialize, Serializer, ser::SerializeSeq};
 use slop_streamer_proc_macros::contract_trait;
+
+use crate::openai_compat::endpoint::responses::{
+    request::input_content::MessageContent,
+    stream::stream_item::{
+        Annotation, ContentPart, FunctionCallItem, ItemStatus, MessageItem, OutputItem,
+        ReasoningItem, SummaryContent,
+    },
+};
+
 mod private {
     pub trait Sealed {}
 }
@@ -25,6 +34,15 @@ pub trait AsInputItem {
 }
 erased_serde::serialize_trait_object!(InputItem);
 
+impl<T> AsInputItem for &T
+where
+    T: AsInputItem + ?Sized,
+{
+    fn erase_variant(&self) -> &dyn InputItem {
+        <T as AsInputItem>::erase_variant(self)
+    }
+}
+
 pub trait InputItemCollection {
     fn serialize_items<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
 
@@ -109,14 +127,14 @@ contract_trait!(
     #[impl_traits(InputItem, AsInputItem)]
     #[wrapper(Message)]
     pub trait InputMessage {
-        content: Cow<'_, str>,
+        content: MessageContent<'_>,
         role: Role,
     }
 );
 
 impl InputMessage for str {
-    fn content(&self) -> Cow<'_, str> {
-        Cow::Borrowed(self)
+    fn content(&self) -> MessageContent<'_> {
+        MessageContent::Text(Cow::Borrowed(self))
     }
 
     fn role(&self) -> Role {
@@ -125,7 +143,7 @@ impl InputMessage for str {
 }
 
 impl InputMessage for String {
-    fn content(&self) -> Cow<'_, str> {
+    fn content(&self) -> MessageContent<'_> {
         <str as InputMessage>::content(self)
     }
     fn role(&self) -> Role {
@@ -169,6 +187,7 @@ contract_trait!(
         id: Option<Cow<'_, str>> = None,
         #[skip_serializing_if(Option::is_none)]
         status: Option<Status> = None,
+        const "type" = "function_call",
     }
 );
 
@@ -186,6 +205,243 @@ contract_trait!(
     }
 );
 
+/// `{"type": "output_text", "text", "annotations"}`, the content of an [`InputOutputMessage`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(tag = "type", rename = "output_text")]
+pub struct OutputText<'a> {
+    pub text: Cow<'a, str>,
+    pub annotations: Vec<OutputAnnotation<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputAnnotation<'a> {
+    UrlCitation {
+        url: Cow<'a, str>,
+        title: Cow<'a, str>,
+        start_index: u32,
+        end_index: u32,
+    },
+}
+
+// A previous assistant turn, as opposed to InputMessage which is for things you're saying to the model
+contract_trait!(
+    #[impl_traits(InputItem, AsInputItem)]
+    #[wrapper(OutputMessage)]
+    pub trait InputOutputMessage {
+        id: Cow<'_, str>,
+        content: Vec<OutputText<'_>>,
+        #[skip_serializing_if(Option::is_none)]
+        status: Option<Status> = None,
+        const "role" = "assistant",
+        const "type" = "message",
+    }
+);
+
+/// `{"type": "summary_text", "text"}`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(tag = "type", rename = "summary_text")]
+pub struct SummaryText<'a> {
+    pub text: Cow<'a, str>,
+}
+
+/// `{"type": "reasoning_text", "text"}`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(tag = "type", rename = "reasoning_text")]
+pub struct ReasoningText<'a> {
+    pub text: Cow<'a, str>,
+}
+
+contract_trait!(
+    #[impl_traits(InputItem, AsInputItem)]
+    #[wrapper(Reasoning)]
+    pub trait InputReasoning {
+        id: Cow<'_, str>,
+        summary: Vec<SummaryText<'_>>,
+        #[skip_serializing_if(Vec::is_empty)]
+        content: Vec<ReasoningText<'_>> = Vec::new(),
+        // Needed to hand the reasoning back to providers that don't store it, see the `include` request field
+        #[skip_serializing_if(Option::is_none)]
+        encrypted_content: Option<Cow<'_, str>> = None,
+        const "type" = "reasoning",
+    }
+);
+
+// Replaying a previous response's output as the next request's input
+
+impl From<ItemStatus> for Status {
+    fn from(value: ItemStatus) -> Self {
+        match value {
+            ItemStatus::InProgress | ItemStatus::Searching | ItemStatus::Queued => {
+                Status::InProgress
+            }
+            ItemStatus::Completed => Status::Completed,
+            ItemStatus::Failed | ItemStatus::Incomplete | ItemStatus::Cancelled => {
+                Status::Incomplete
+            }
+        }
+    }
+}
+
+/// Parts other than `output_text` (and annotations other than url citations) have no input form here and are left out
+impl<T> InputOutputMessage for MessageItem<T>
+where
+    T: AsRef<str>,
+{
+    fn id(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self.id.as_ref())
+    }
+
+    fn content(&self) -> Vec<OutputText<'_>> {
+        self.content
+            .iter()
+            .filter_map(|part| match part {
+                ContentPart::OutputText(part) => Some(OutputText {
+                    text: Cow::Borrowed(part.text.as_ref()),
+                    annotations: part
+                        .annotations
+                        .iter()
+                        .filter_map(|annotation| match annotation {
+                            Annotation::UrlCitation(citation) => {
+                                Some(OutputAnnotation::UrlCitation {
+                                    url: Cow::Borrowed(citation.url.as_ref()),
+                                    title: Cow::Borrowed(citation.title.as_ref()),
+                                    start_index: citation.start_index,
+                                    end_index: citation.end_index,
+                                })
+                            }
+                            Annotation::Unknown { .. } => None,
+                        })
+                        .collect(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn status(&self) -> Option<Status> {
+        Some(self.status.into())
+    }
+}
+
+impl<T> InputReasoning for ReasoningItem<T>
+where
+    T: AsRef<str>,
+{
+    fn id(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self.id.as_ref())
+    }
+
+    fn summary(&self) -> Vec<SummaryText<'_>> {
+        self.summary
+            .iter()
+            .map(|part| match &part.content {
+                SummaryContent::SummaryText(part) => SummaryText {
+                    text: Cow::Borrowed(part.text.as_ref()),
+                },
+            })
+            .collect()
+    }
+
+    fn content(&self) -> Vec<ReasoningText<'_>> {
+        self.content
+            .iter()
+            .filter_map(|part| match part {
+                ContentPart::ReasoningText(part) => Some(ReasoningText {
+                    text: Cow::Borrowed(part.text.as_ref()),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn encrypted_content(&self) -> Option<Cow<'_, str>> {
+        self.encrypted_content
+            .as_ref()
+            .map(|encrypted| Cow::Borrowed(encrypted.as_ref()))
+    }
+}
+
+impl<T> InputFunctioncall for FunctionCallItem<T>
+where
+    T: AsRef<str>,
+{
+    fn arguments(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self.arguments.as_ref())
+    }
+
+    fn call_id(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self.call_id.as_ref())
+    }
+
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self.name.as_ref())
+    }
+
+    fn id(&self) -> Option<Cow<'_, str>> {
+        self.id.as_ref().map(|id| Cow::Borrowed(id.as_ref()))
+    }
+
+    fn status(&self) -> Option<Status> {
+        Some(self.status.into())
+    }
+}
+
+macro_rules! impl_as_input_item {
+    ($($ty:ident),*) => {
+        $(
+            impl<T> AsInputItem for $ty<T>
+            where
+                T: AsRef<str>,
+            {
+                fn erase_variant(&self) -> &dyn InputItem {
+                    self.as_wrapper_ref()
+                }
+            }
+        )*
+    };
+}
+
+impl_as_input_item!(MessageItem, ReasoningItem, FunctionCallItem);
+
+/// Items with no contract of their own are sent back exactly as they were received
+#[repr(transparent)]
+struct Verbatim<T>(T);
+
+impl<T> Verbatim<T> {
+    fn from_ref(value: &T) -> &Self {
+        // Safety: Verbatim is #[repr(transparent)], so &T and &Verbatim<T> have identical layout
+        unsafe { &*(std::ptr::from_ref(value) as *const Verbatim<T>) }
+    }
+}
+
+impl<T> Serialize for Verbatim<T>
+where
+    T: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<T> private::Sealed for Verbatim<OutputItem<T>> {}
+
+impl<T> InputItem for Verbatim<OutputItem<T>> where T: Serialize + Deref<Target = str> {}
+
+impl<T> AsInputItem for OutputItem<T>
+where
+    T: AsRef<str> + Deref<Target = str> + Serialize,
+{
+    fn erase_variant(&self) -> &dyn InputItem {
+        match self {
+            OutputItem::Message(message) => message.erase_variant(),
+            OutputItem::Reasoning(reasoning) => reasoning.erase_variant(),
+            OutputItem::FunctionCall(call) => call.erase_variant(),
+            OutputItem::WebSearchCall(_) | OutputItem::Unknown { .. } => Verbatim::from_ref(self),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -197,8 +453,8 @@ mod tests {
         }
 
         impl InputMessage for MyMsg {
-            fn content(&self) -> Cow<'_, str> {
-                Cow::Borrowed(&self.content)
+            fn content(&self) -> MessageContent<'_> {
+                MessageContent::Text(Cow::Borrowed(&self.content))
             }
             fn role(&self) -> Role {
                 Role::User
@@ -219,4 +475,95 @@ mod tests {
         let json = serde_json::to_string(wrapper_ref).unwrap();
         assert_eq!(&json, r#"{"content":"test","role":"user"}"#);
     }
+
+    #[test]
+    fn test_output_items_replay_as_input() {
+        use crate::openai_compat::endpoint::responses::stream::stream_item::{
+            OutputTextPart, SummaryPart, SummaryTextPart, UrlCitation, WebSearchCallItem,
+        };
+
+        let output: Vec<OutputItem<String>> = vec![
+            OutputItem::Reasoning(ReasoningItem {
+                id: "rs_1".to_string(),
+                summary: vec![SummaryPart {
+                    content: SummaryContent::SummaryText(SummaryTextPart {
+                        text: "thinking".to_string(),
+                    }),
+                }],
+                content: Vec::new(),
+                encrypted_content: Some("gAAAA".to_string()),
+            }),
+            OutputItem::WebSearchCall(WebSearchCallItem {
+                id: "ws_1".to_string(),
+                status: ItemStatus::Completed,
+                action: None,
+            }),
+            OutputItem::Message(MessageItem {
+                id: "msg_1".to_string(),
+                status: ItemStatus::Completed,
+                content: vec![ContentPart::OutputText(OutputTextPart {
+                    text: "see here".to_string(),
+                    annotations: vec![Annotation::UrlCitation(UrlCitation {
+                        url: "https://example.com/".to_string(),
+                        title: "Example".to_string(),
+                        start_index: 4,
+                        end_index: 8,
+                    })],
+                })],
+            }),
+            OutputItem::FunctionCall(FunctionCallItem {
+                id: Some("fc_1".to_string()),
+                call_id: "call_1".to_string(),
+                name: "get_current_weather".to_string(),
+                arguments: "{}".to_string(),
+                status: ItemStatus::Completed,
+            }),
+        ];
+
+        let mut json = Vec::new();
+        output
+            .iter()
+            .collect::<Vec<_>>()
+            .serialize_items(&mut serde_json::Serializer::new(&mut json))
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {
+                    "type": "reasoning",
+                    "id": "rs_1",
+                    "summary": [{"type": "summary_text", "text": "thinking"}],
+                    "encrypted_content": "gAAAA",
+                },
+                {"type": "web_search_call", "id": "ws_1", "status": "completed"},
+                {
+                    "type": "message",
+                    "role": "assistant",
+                    "id": "msg_1",
+                    "status": "completed",
+                    "content": [{
+                        "type": "output_text",
+                        "text": "see here",
+                        "annotations": [{
+                            "type": "url_citation",
+                            "url": "https://example.com/",
+                            "title": "Example",
+                            "start_index": 4,
+                            "end_index": 8,
+                        }],
+                    }],
+                },
+                {
+                    "type": "function_call",
+                    "id": "fc_1",
+                    "call_id": "call_1",
+                    "name": "get_current_weather",
+                    "arguments": "{}",
+                    "status": "completed",
+                },
+            ])
+        );
+    }
 }
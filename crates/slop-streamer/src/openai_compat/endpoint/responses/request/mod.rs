@@ -1,10 +1,20 @@
-use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
 
+use serde::{Deserialize, Serialize, Serializer};
+
+pub mod builder;
+pub mod input_content;
 pub mod input_type;
+pub mod openrouter;
+pub mod reasoning;
+pub mod text_format;
 pub mod tool_choice;
+pub mod tools;
+
+pub use builder::RequestBuilder;
 
 #[derive(Debug, Clone, Serialize)]
-pub struct Request<I, M> {
+pub struct Request<I, M, T = ()> {
     #[serde(
         bound(serialize = "I: input_type::InputItemCollection"),
         serialize_with = "input_type::InputItemCollection::serialize_items"
@@ -17,8 +27,377 @@ pub struct Request<I, M> {
     pub model: M,
     pub parallel_tool_calls: bool,
     pub stream: bool,
+    #[serde(
+        bound(serialize = "T: tools::ToolCollection"),
+        serialize_with = "tools::ToolCollection::serialize_tools",
+        skip_serializing_if = "tools::ToolCollection::is_empty"
+    )]
+    pub tools: T,
+    /// Everything else, left unset and skipped when serializing unless the builder sets it
+    #[serde(flatten)]
+    pub options: RequestOptions,
 }
 
-fn serialize_as_ref_str<T: AsRef<str>, S: Serializer>(t: &T, ser: S) -> Result<S::Ok, S::Error> {
+impl<I, M> Request<I, M> {
+    /// Start building a request, see [`RequestBuilder`] for the defaults
+    pub fn builder(input: I, model: M) -> RequestBuilder<I, M> {
+        RequestBuilder::new(input, model)
+    }
+}
+
+/// The optional fields of [`Request`], kept apart from the generic ones so changing the tools type doesn't have to touch them
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RequestOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tool_calls: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_logprobs: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<reasoning::ReasoningConfig>,
+    /// Extra data to include in the response, see [`Include`]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<Include>,
+    /// Ask for JSON, optionally matching a schema, see [`text_format::TextFormat`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<text_format::TextConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncation: Option<Truncation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_tier: Option<ServiceTier>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safety_identifier: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_cache_key: Option<String>,
+    /// `models` and `provider`, which only OpenRouter understands. Nothing is sent while this is `None`.
+    #[serde(flatten)]
+    pub openrouter: Option<openrouter::OpenRouterOptions>,
+}
+
+/// Extra output the response leaves out unless asked for
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Include {
+    /// The encrypted reasoning on reasoning items, which is what lets them be sent back as input with `store: false`
+    #[serde(rename = "reasoning.encrypted_content")]
+    ReasoningEncryptedContent,
+    #[serde(rename = "message.output_text.logprobs")]
+    OutputTextLogprobs,
+    #[serde(rename = "web_search_call.action.sources")]
+    WebSearchSources,
+    #[serde(rename = "file_search_call.results")]
+    FileSearchResults,
+    #[serde(rename = "code_interpreter_call.outputs")]
+    CodeInterpreterOutputs,
+}
+
+impl std::fmt::Display for Include {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Include::ReasoningEncryptedContent => "reasoning.encrypted_content",
+            Include::OutputTextLogprobs => "message.output_text.logprobs",
+            Include::WebSearchSources => "web_search_call.action.sources",
+            Include::FileSearchResults => "file_search_call.results",
+            Include::CodeInterpreterOutputs => "code_interpreter_call.outputs",
+        }
+        .fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Truncation {
+    Auto,
+    Disabled,
+}
+
+impl std::fmt::Display for Truncation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Truncation::Auto => "auto",
+            Truncation::Disabled => "disabled",
+        }
+        .fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceTier {
+    Auto,
+    Default,
+    Flex,
+    Scale,
+    Priority,
+}
+
+impl std::fmt::Display for ServiceTier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServiceTier::Auto => "auto",
+            ServiceTier::Default => "default",
+            ServiceTier::Flex => "flex",
+            ServiceTier::Scale => "scale",
+            ServiceTier::Priority => "priority",
+        }
+        .fmt(f)
+    }
+}
+
+pub(crate) fn serialize_as_ref_str<T: AsRef<str>, S: Serializer>(
+    t: &T,
+    ser: S,
+) -> Result<S::Ok, S::Error> {
     ser.serialize_str(t.as_ref())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::openai_compat::endpoint::models::catalog::{ModelInfo, Parameter};
+
+    #[test]
+    fn test_minimal_request_skips_unset_fields() {
+        let request = Request::builder(["hello".to_string()], "openai/gpt-5.2").build();
+
+        let json = serde_json::to_string(&request).unwrap();
+        assert_eq!(
+            &json,
+            r#"{"input":[{"content":"hello","role":"user"}],"model":"openai/gpt-5.2","parallel_tool_calls":true,"stream":true}"#
+        );
+    }
+
+    #[test]
+    fn test_builder_sets_tools() {
+        #[derive(schemars::JsonSchema)]
+        struct CalculateInput {
+            #[allow(dead_code)]
+            expression: String,
+        }
+
+        struct Calculate;
+
+        impl crate::tool::Tool for Calculate {
+            type Input = CalculateInput;
+
+            fn name(&self) -> &str {
+                "calculate"
+            }
+
+            fn description(&self) -> &str {
+                "Perform a mathematical calculation"
+            }
+        }
+
+        let tools: Vec<Box<dyn crate::tool::ToolDefinition>> = vec![Box::new(Calculate)];
+        let request = Request::builder(Vec::<String>::new(), "openai/gpt-5.2")
+            .tools(tools)
+            .build();
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["tools"][0]["type"], "function");
+        assert_eq!(json["tools"][0]["name"], "calculate");
+        assert_eq!(
+            json["tools"][0]["parameters"]["properties"]["expression"]["type"],
+            "string"
+        );
+    }
+
+    #[test]
+    fn test_builder_sets_sampling_fields() {
+        let request = Request::builder(Vec::<String>::new(), "openai/gpt-5.2")
+            .instructions("be terse")
+            .temperature(0.7)
+            .top_p(1.0)
+            .max_output_tokens(3000)
+            .metadata("job", "nightly")
+            .truncation(Truncation::Disabled)
+            .service_tier(ServiceTier::Auto)
+            .store(false)
+            .build();
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["instructions"], "be terse");
+        assert_eq!(json["temperature"], 0.7);
+        assert_eq!(json["top_p"], 1.0);
+        assert_eq!(json["max_output_tokens"], 3000);
+        assert_eq!(json["metadata"]["job"], "nightly");
+        assert_eq!(json["truncation"], "disabled");
+        assert_eq!(json["service_tier"], "auto");
+        assert_eq!(json["store"], false);
+        assert!(json.get("presence_penalty").is_none());
+    }
+
+    #[test]
+    fn test_builder_validates_against_model() {
+        let model: ModelInfo<&str> = serde_json::from_str(
+            r#"{"id":"openai/gpt-5.2","supported_parameters":["max_tokens","tools"]}"#,
+        )
+        .unwrap();
+
+        let builder =
+            Request::builder(Vec::<String>::new(), "openai/gpt-5.2").max_output_tokens(3000);
+        assert!(builder.validate_for(&model).is_ok());
+
+        let err = builder
+            .temperature(0.7)
+            .top_p(1.0)
+            .validate_for(&model)
+            .unwrap_err();
+        assert_eq!(err.parameters, [Parameter::Temperature, Parameter::TopP]);
+    }
+
+    #[test]
+    fn test_openrouter_options_are_flattened() {
+        use openrouter::{MaxPrice, ProviderPreferences, ProviderSort, Quantization};
+
+        let request = Request::builder(Vec::<String>::new(), "qwen/qwen3-8b")
+            .fallback_models(["qwen/qwen3-14b", "meta-llama/llama-3.1-8b-instruct"])
+            .provider(
+                ProviderPreferences::new()
+                    .with_order(["deepinfra", "together"])
+                    .with_allow_fallbacks(false)
+                    .with_quantizations([Quantization::Fp8, Quantization::Bf16])
+                    .with_sort(ProviderSort::Throughput)
+                    .with_max_price(MaxPrice {
+                        prompt: Some(0.1),
+                        ..MaxPrice::default()
+                    }),
+            )
+            .build();
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["models"][1], "meta-llama/llama-3.1-8b-instruct");
+        assert_eq!(
+            json["provider"],
+            serde_json::json!({
+                "order": ["deepinfra", "together"],
+                "allow_fallbacks": false,
+                "quantizations": ["fp8", "bf16"],
+                "sort": "throughput",
+                "max_price": {"prompt": 0.1},
+            })
+        );
+        assert!(json.get("openrouter").is_none());
+
+        // Only a provider, no fallbacks
+        let request = Request::builder(Vec::<String>::new(), "qwen/qwen3-8b")
+            .provider(ProviderPreferences::new().with_only(["deepinfra"]))
+            .build();
+        let json = serde_json::to_value(&request).unwrap();
+        assert!(json.get("models").is_none());
+        assert_eq!(json["provider"]["only"][0], "deepinfra");
+    }
+
+    #[test]
+    fn test_builder_sets_reasoning_and_include() {
+        use openrouter::OpenRouterReasoning;
+        use reasoning::{ReasoningConfig, ReasoningEffort, ReasoningSummary};
+
+        let builder = Request::builder(Vec::<String>::new(), "openai/gpt-5.2")
+            .reasoning(ReasoningConfig::new().with_summary(ReasoningSummary::Auto))
+            .reasoning_effort(ReasoningEffort::High)
+            .store(false)
+            .include(Include::ReasoningEncryptedContent)
+            .include(Include::ReasoningEncryptedContent);
+
+        let request = builder.clone().build();
+        assert_eq!(
+            request.options.reasoning.unwrap().openrouter,
+            OpenRouterReasoning::default()
+        );
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            json["reasoning"],
+            serde_json::json!({"effort": "high", "summary": "auto"})
+        );
+        assert_eq!(
+            json["include"],
+            serde_json::json!(["reasoning.encrypted_content"])
+        );
+
+        // OpenRouter's token budget form
+        let request = Request::builder(Vec::<String>::new(), "anthropic/claude-sonnet-4.5")
+            .reasoning(
+                ReasoningConfig::new()
+                    .with_max_tokens(2000)
+                    .with_exclude(true),
+            )
+            .build();
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(
+            json["reasoning"],
+            serde_json::json!({"max_tokens": 2000, "exclude": true})
+        );
+        assert!(json.get("include").is_none());
+
+        let model: ModelInfo<&str> =
+            serde_json::from_str(r#"{"id":"m","supported_parameters":["tools"]}"#).unwrap();
+        assert_eq!(
+            builder.validate_for(&model).unwrap_err().parameters,
+            [Parameter::Reasoning]
+        );
+    }
+
+    #[test]
+    fn test_json_schema_round_trip() {
+        use crate::openai_compat::endpoint::responses::stream::stream_item::OutputTextDoneData;
+
+        #[derive(Debug, PartialEq, serde::Deserialize, schemars::JsonSchema)]
+        struct Verdict<'a> {
+            approved: bool,
+            reason: &'a str,
+            confidence: Option<f64>,
+        }
+
+        let builder = Request::builder(["Review this".to_string()], "openai/gpt-5.2")
+            .json_schema::<Verdict>();
+        let json = serde_json::to_value(builder.clone().build()).unwrap();
+        assert_eq!(json["text"]["format"]["type"], "json_schema");
+        assert_eq!(json["text"]["format"]["strict"], true);
+        assert_eq!(
+            json["text"]["format"]["schema"]["required"],
+            serde_json::json!(["approved", "confidence", "reason"])
+        );
+
+        let model: ModelInfo<&str> =
+            serde_json::from_str(r#"{"id":"m","supported_parameters":["response_format"]}"#)
+                .unwrap();
+        assert_eq!(
+            builder.validate_for(&model).unwrap_err().parameters,
+            [Parameter::StructuredOutputs]
+        );
+
+        let done = OutputTextDoneData {
+            item_id: "msg_1",
+            output_index: 0,
+            content_index: 0,
+            text: r#"{"approved":true,"reason":"looks fine","confidence":null}"#,
+            sequence_number: 12,
+        };
+        assert_eq!(
+            done.parse_json::<Verdict>().unwrap(),
+            Verdict {
+                approved: true,
+                reason: "looks fine",
+                confidence: None,
+            }
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! OpenRouter's extensions to the request body, kept in one optional field so requests to other servers never carry them
+
+use serde::{Deserialize, Serialize};
+
+/// Flattened into the request, so `models` and `provider` sit next to the OpenAI fields
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OpenRouterOptions {
+    /// Tried in order when the request's `model` is unavailable, rate limited or refuses the request
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<ProviderPreferences>,
+}
+
+/// OpenRouter's additions to the `reasoning` object, flattened into [`ReasoningConfig`](super::reasoning::ReasoningConfig)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenRouterReasoning {
+    /// A token budget for reasoning, an alternative to `effort`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+    /// Reason but leave the reasoning out of the response. It is still billed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<bool>,
+    /// Turn reasoning on at the model's default effort, or off where the model allows it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+/// How OpenRouter picks among the providers serving the model. Provider names are slugs like `openai`, `deepinfra/turbo` or `together`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderPreferences {
+    /// Tried first, in this order, before the rest
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub order: Vec<String>,
+    /// `false` to fail rather than use providers outside `order`, defaults to `true`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_fallbacks: Option<bool>,
+    /// Only use providers that support every parameter in the request, instead of dropping the ones they don't
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub require_parameters: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_collection: Option<DataCollection>,
+    /// The only providers to use
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub only: Vec<String>,
+    /// Providers to never use
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+    /// Only use providers serving the model at one of these precisions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub quantizations: Vec<Quantization>,
+    /// Order providers by this rather than OpenRouter's price and uptime balancing, disables load balancing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<ProviderSort>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_price: Option<MaxPrice>,
+}
+
+impl ProviderPreferences {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_order<S: Into<String>>(mut self, order: impl IntoIterator<Item = S>) -> Self {
+        self.order = order.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_allow_fallbacks(mut self, allow_fallbacks: bool) -> Self {
+        self.allow_fallbacks = Some(allow_fallbacks);
+        self
+    }
+
+    pub fn with_require_parameters(mut self, require_parameters: bool) -> Self {
+        self.require_parameters = Some(require_parameters);
+        self
+    }
+
+    pub fn with_data_collection(mut self, data_collection: DataCollection) -> Self {
+        self.data_collection = Some(data_collection);
+        self
+    }
+
+    pub fn with_only<S: Into<String>>(mut self, only: impl IntoIterator<Item = S>) -> Self {
+        self.only = only.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_ignore<S: Into<String>>(mut self, ignore: impl IntoIterator<Item = S>) -> Self {
+        self.ignore = ignore.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_quantizations(
+        mut self,
+        quantizations: impl IntoIterator<Item = Quantization>,
+    ) -> Self {
+        self.quantizations = quantizations.into_iter().collect();
+        self
+    }
+
+    pub fn with_sort(mut self, sort: ProviderSort) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    pub fn with_max_price(mut self, max_price: MaxPrice) -> Self {
+        self.max_price = Some(max_price);
+        self
+    }
+}
+
+/// Whether providers that may store or train on requests can be used
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum DataCollection {
+    Allow,
+    Deny,
+}
+
+impl std::fmt::Display for DataCollection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataCollection::Allow => "allow",
+            DataCollection::Deny => "deny",
+        }
+        .fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Quantization {
+    Int4,
+    Int8,
+    Fp4,
+    Fp6,
+    Fp8,
+    Fp16,
+    Bf16,
+    Fp32,
+    Unknown,
+}
+
+impl std::fmt::Display for Quantization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Quantization::Int4 => "int4",
+            Quantization::Int8 => "int8",
+            Quantization::Fp4 => "fp4",
+            Quantization::Fp6 => "fp6",
+            Quantization::Fp8 => "fp8",
+            Quantization::Fp16 => "fp16",
+            Quantization::Bf16 => "bf16",
+            Quantization::Fp32 => "fp32",
+            Quantization::Unknown => "unknown",
+        }
+        .fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderSort {
+    /// Cheapest first
+    Price,
+    /// Most tokens per second first
+    Throughput,
+    /// Lowest time to first token first
+    Latency,
+}
+
+impl std::fmt::Display for ProviderSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProviderSort::Price => "price",
+            ProviderSort::Throughput => "throughput",
+            ProviderSort::Latency => "latency",
+        }
+        .fmt(f)
+    }
+}
+
+/// The most to pay, in USD. Unlike the models list, token prices here are per million tokens.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MaxPrice {
+    /// Per million input tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<f64>,
+    /// Per million output tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion: Option<f64>,
+    /// Per request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request: Option<f64>,
+    /// Per image
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<f64>,
+}
@@ -0,0 +1,96 @@
+//! The `reasoning` request field. OpenAI reads `effort` and `summary`, OpenRouter also takes `max_tokens`, `exclude` and `enabled`
+//! and translates them for models that budget reasoning in tokens rather than effort levels.
+
+use serde::{Deserialize, Serialize};
+
+use crate::openai_compat::endpoint::responses::request::openrouter::OpenRouterReasoning;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReasoningConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effort: Option<ReasoningEffort>,
+    /// Stream a summary of the reasoning, for models that don't expose the reasoning itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<ReasoningSummary>,
+    /// `max_tokens`, `exclude` and `enabled`, which OpenAI rejects. Nothing is sent while they are all unset.
+    #[serde(flatten)]
+    pub openrouter: OpenRouterReasoning,
+}
+
+impl ReasoningConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_effort(mut self, effort: ReasoningEffort) -> Self {
+        self.effort = Some(effort);
+        self
+    }
+
+    pub fn with_summary(mut self, summary: ReasoningSummary) -> Self {
+        self.summary = Some(summary);
+        self
+    }
+
+    /// OpenRouter only, see [`OpenRouterReasoning::max_tokens`]
+    pub fn with_max_tokens(mut self, max_tokens: u64) -> Self {
+        self.openrouter.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// OpenRouter only, see [`OpenRouterReasoning::exclude`]
+    pub fn with_exclude(mut self, exclude: bool) -> Self {
+        self.openrouter.exclude = Some(exclude);
+        self
+    }
+
+    /// OpenRouter only, see [`OpenRouterReasoning::enabled`]
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.openrouter.enabled = Some(enabled);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    None,
+    Minimal,
+    Low,
+    Medium,
+    High,
+    Xhigh,
+}
+
+impl std::fmt::Display for ReasoningEffort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReasoningEffort::None => "none",
+            ReasoningEffort::Minimal => "minimal",
+            ReasoningEffort::Low => "low",
+            ReasoningEffort::Medium => "medium",
+            ReasoningEffort::High => "high",
+            ReasoningEffort::Xhigh => "xhigh",
+        }
+        .fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningSummary {
+    Auto,
+    Concise,
+    Detailed,
+}
+
+impl std::fmt::Display for ReasoningSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReasoningSummary::Auto => "auto",
+            ReasoningSummary::Concise => "concise",
+            ReasoningSummary::Detailed => "detailed",
+        }
+        .fmt(f)
+    }
+}
@@ -0,0 +1,192 @@
+//! The `text` request field, which is how the Responses endpoint is asked for JSON, optionally matching a schema
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::openai_compat::endpoint::responses::response::JsonSchemaFormat;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TextConfig {
+    pub format: TextFormat,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TextFormat {
+    /// The default, free form text
+    Text,
+    /// Any valid JSON, the instructions or input still have to ask for JSON
+    JsonObject,
+    JsonSchema(JsonSchemaFormat<String>),
+}
+
+impl TextFormat {
+    /// A strict `json_schema` format for `T`, with the schema rewritten by [`strictify`] and named after `T`.
+    /// Strict mode needs the root to be an object, so `T` should be a struct.
+    pub fn json_schema<T>() -> Self
+    where
+        T: JsonSchema,
+    {
+        let mut schema = schemars::schema_for!(T).to_value();
+        strictify(&mut schema);
+
+        TextFormat::JsonSchema(JsonSchemaFormat {
+            name: format_name(&T::schema_name()),
+            description: None,
+            schema,
+            strict: Some(true),
+        })
+    }
+}
+
+/// Names are limited to 64 of `[a-zA-Z0-9_-]`, schemars names generic types like `Wrapper_for_Inner`
+fn format_name(schema_name: &str) -> String {
+    schema_name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => c,
+            _ => '_',
+        })
+        .take(64)
+        .collect()
+}
+
+/// Keywords strict mode rejects, or that only make sense for validation the provider won't do
+const UNSUPPORTED_KEYWORDS: &[&str] = &[
+    "$schema",
+    "format",
+    "default",
+    "examples",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+    "contentEncoding",
+    "contentMediaType",
+];
+
+/// Rewrite a JSON schema into the subset OpenAI's strict mode accepts: every object lists all its properties as required
+/// and has `additionalProperties: false`, `oneOf` becomes `anyOf`, and [`UNSUPPORTED_KEYWORDS`] are dropped.
+///
+/// `Option` fields stay optional in meaning since schemars already allows `null` for them.
+pub fn strictify(schema: &mut Value) {
+    let Value::Object(schema) = schema else {
+        return;
+    };
+
+    for keyword in UNSUPPORTED_KEYWORDS {
+        schema.remove(*keyword);
+    }
+    if let Some(one_of) = schema.remove("oneOf") {
+        schema.insert("anyOf".to_string(), one_of);
+    }
+
+    if let Some(Value::Object(properties)) = schema.get_mut("properties") {
+        properties.values_mut().for_each(strictify);
+        let required = properties.keys().cloned().map(Value::String).collect();
+        schema.insert("required".to_string(), Value::Array(required));
+        schema.insert("additionalProperties".to_string(), Value::Bool(false));
+    }
+
+    // Subschemas in lists
+    for keyword in ["anyOf", "allOf", "prefixItems"] {
+        if let Some(Value::Array(subschemas)) = schema.get_mut(keyword) {
+            subschemas.iter_mut().for_each(strictify);
+        }
+    }
+    // Subschemas in maps
+    for keyword in ["$defs", "definitions"] {
+        if let Some(Value::Object(subschemas)) = schema.get_mut(keyword) {
+            subschemas.values_mut().for_each(strictify);
+        }
+    }
+    // Single subschemas, `additionalProperties` is only one when it's not a bool
+    for keyword in ["items", "additionalProperties", "not", "contains"] {
+        if let Some(subschema) = schema.get_mut(keyword) {
+            strictify(subschema);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema)]
+    struct Ticket {
+        /// Short summary
+        title: String,
+        #[serde(default)]
+        priority: Priority,
+        due: Option<Due>,
+        labels: Vec<String>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema, Default, serde::Serialize)]
+    enum Priority {
+        #[default]
+        Low,
+        High,
+    }
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema)]
+    enum Due {
+        Date { format: String },
+        Never,
+    }
+
+    #[test]
+    fn test_json_schema_is_strict() {
+        let TextFormat::JsonSchema(format) = TextFormat::json_schema::<Ticket>() else {
+            panic!("Expected a json_schema format");
+        };
+        assert_eq!(format.name, "Ticket");
+        assert_eq!(format.strict, Some(true));
+
+        let schema = &format.schema;
+        assert!(schema.get("$schema").is_none());
+        assert_eq!(
+            schema["required"],
+            serde_json::json!(["due", "labels", "priority", "title"])
+        );
+        assert_eq!(schema["additionalProperties"], false);
+        assert!(schema["properties"]["priority"].get("default").is_none());
+        assert_eq!(
+            schema["properties"]["title"]["description"],
+            "Short summary"
+        );
+
+        // The data carrying enum is a oneOf, and its variant objects get the same treatment
+        let due = &schema["$defs"]["Due"];
+        assert!(due.get("oneOf").is_none());
+        let date = &due["anyOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|variant| variant["properties"].get("Date").is_some())
+            .unwrap()["properties"]["Date"];
+        assert_eq!(date["additionalProperties"], false);
+        // A property named like a keyword isn't removed
+        assert!(date["properties"].get("format").is_some());
+
+        let json = serde_json::to_value(TextConfig {
+            format: TextFormat::JsonSchema(format),
+        })
+        .unwrap();
+        assert_eq!(json["format"]["type"], "json_schema");
+        assert_eq!(json["format"]["name"], "Ticket");
+    }
+
+    #[test]
+    fn test_format_name() {
+        assert_eq!(format_name("Wrapper_for_Inner"), "Wrapper_for_Inner");
+        assert_eq!(
+            format_name("Array_of_Nullable<str>"),
+            "Array_of_Nullable_str_"
+        );
+        assert_eq!(format_name(&"a".repeat(100)).len(), 64);
+    }
+}
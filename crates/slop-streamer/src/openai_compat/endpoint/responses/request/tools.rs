@@ -0,0 +1,144 @@
+//! Serialization of [`Tool`](crate::tool::Tool)s into the Responses `tools` array
+
+use serde::{Serialize, Serializer, ser::SerializeMap};
+
+use crate::tool::{AsToolDefinition, ToolDefinition};
+
+const_str!(pub struct FunctionStr("function"));
+
+/// A tool definition in the shape the Responses endpoint expects, `{"type": "function", "name", "description", "parameters", "strict"}`
+#[derive(Clone, Copy)]
+pub struct FunctionTool<'a>(pub &'a dyn ToolDefinition);
+
+impl Serialize for FunctionTool<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(5))?;
+        map.serialize_entry("type", &FunctionStr)?;
+        map.serialize_entry("name", self.0.name())?;
+        map.serialize_entry("description", self.0.description())?;
+        map.serialize_entry("parameters", &self.0.parameters())?;
+        map.serialize_entry("strict", &self.0.strict())?;
+        map.end()
+    }
+}
+
+impl std::fmt::Debug for FunctionTool<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FunctionTool")
+            .field("name", &self.0.name())
+            .finish_non_exhaustive()
+    }
+}
+
+pub trait ToolCollection {
+    /// Every tool in the collection, in the order they're sent
+    fn tool_definitions(&self) -> impl Iterator<Item = &dyn ToolDefinition>;
+
+    fn serialize_tools<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.tool_definitions().map(FunctionTool))
+    }
+
+    /// Empty collections are left out of the request entirely
+    fn is_empty(&self) -> bool {
+        self.tool_definitions().next().is_none()
+    }
+}
+
+/// No tools, the default for [`Request`](super::Request)
+impl ToolCollection for () {
+    fn tool_definitions(&self) -> impl Iterator<Item = &dyn ToolDefinition> {
+        std::iter::empty()
+    }
+}
+
+macro_rules! impl_tool_collection {
+    ($($ty:ty),*) => {
+        $(
+            impl<T> ToolCollection for $ty
+            where
+                T: AsToolDefinition,
+            {
+                fn tool_definitions(&self) -> impl Iterator<Item = &dyn ToolDefinition> {
+                    self.iter().map(|tool| tool.as_tool_definition())
+                }
+            }
+        )*
+    };
+}
+
+impl_tool_collection!(
+    Vec<T>,
+    &[T],
+    Box<[T]>,
+    std::sync::Arc<[T]>,
+    std::rc::Rc<[T]>,
+    [T]
+);
+
+impl<T, const LEN: usize> ToolCollection for [T; LEN]
+where
+    T: AsToolDefinition,
+{
+    fn tool_definitions(&self) -> impl Iterator<Item = &dyn ToolDefinition> {
+        self.as_slice().tool_definitions()
+    }
+}
+
+impl<T> ToolCollection for &T
+where
+    T: ToolCollection,
+{
+    fn tool_definitions(&self) -> impl Iterator<Item = &dyn ToolDefinition> {
+        <T as ToolCollection>::tool_definitions(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use schemars::JsonSchema;
+
+    use super::*;
+    use crate::tool::Tool;
+
+    #[allow(dead_code)]
+    #[derive(JsonSchema)]
+    struct WeatherInput {
+        /// The city and country, e.g. Tokyo, Japan
+        location: String,
+    }
+
+    struct Weather;
+
+    impl Tool for Weather {
+        type Input = WeatherInput;
+
+        fn name(&self) -> &str {
+            "get_current_weather"
+        }
+
+        fn description(&self) -> &str {
+            "Get the current weather in a given location"
+        }
+    }
+
+    #[test]
+    fn test_tool_serializes_as_function() {
+        let mut json = Vec::new();
+        [Weather]
+            .serialize_tools(&mut serde_json::Serializer::new(&mut json))
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+
+        let tool = &json[0];
+        assert_eq!(tool["type"], "function");
+        assert_eq!(tool["name"], "get_current_weather");
+        assert_eq!(
+            tool["description"],
+            "Get the current weather in a given location"
+        );
+        assert_eq!(tool["strict"], serde_json::Value::Null);
+        assert_eq!(tool["parameters"]["type"], "object");
+        assert_eq!(tool["parameters"]["required"][0], "location");
+        assert!(tool["parameters"].get("$schema").is_none());
+    }
+}
@@ -0,0 +1,365 @@
+//! The response object, returned as the body of a `stream: false` request and embedded in the `response.*` lifecycle events
+
+use std::{borrow::Cow, collections::BTreeMap, ops::Deref};
+
+use bytes_utils::Str;
+use serde::{Deserialize, Serialize};
+
+use crate::openai_compat::endpoint::responses::{
+    request::{ServiceTier, Truncation, tool_choice::ToolChoiceMode},
+    stream::stream_item::{
+        ConvertToOwned, ConvertToString, IncompleteDetails, OutputItem, ResponseError,
+        ResponseStatus, UsageInfo, impl_conversion, impl_tagged_deserialize, serialize_unknown,
+    },
+};
+
+/// Only `id` and `status` are required, everything else is optional since providers differ in what they echo back,
+/// and the lifecycle events of some of them only carry a partial object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "T: Serialize + Deref<Target = str>"))]
+pub struct Response<T = Str> {
+    pub id: T,
+    pub status: ResponseStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<T>,
+    /// Unix timestamp in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    /// Unix timestamp in seconds, `None` until the response is done
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<u64>,
+    #[serde(default = "Vec::new")]
+    pub output: Vec<OutputItem<T>>,
+    /// OpenRouter's concatenation of all `output_text`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_text: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<UsageInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ResponseError<T>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub incomplete_details: Option<IncompleteDetails<T>>,
+
+    // The request parameters the response was generated with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<serde_json::Value>,
+    #[serde(default = "Vec::new")]
+    pub tools: Vec<ResponseTool<T>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ResponseToolChoice<T>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_logprobs: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tool_calls: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<ResponseTextConfig<T>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<ResponseReasoning<T>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_response_id: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_tier: Option<ServiceTier>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncation: Option<Truncation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safety_identifier: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_cache_key: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<T>,
+}
+
+impl<T> Response<T> {
+    /// A response with only the required fields set, for building one rather than parsing it
+    pub fn new(id: T, status: ResponseStatus) -> Self {
+        Self {
+            id,
+            status,
+            model: None,
+            created_at: None,
+            completed_at: None,
+            output: Vec::new(),
+            output_text: None,
+            usage: None,
+            error: None,
+            incomplete_details: None,
+            instructions: None,
+            tools: Vec::new(),
+            tool_choice: None,
+            parallel_tool_calls: None,
+            temperature: None,
+            top_p: None,
+            presence_penalty: None,
+            frequency_penalty: None,
+            top_logprobs: None,
+            max_output_tokens: None,
+            max_tool_calls: None,
+            text: None,
+            reasoning: None,
+            metadata: None,
+            background: None,
+            previous_response_id: None,
+            service_tier: None,
+            truncation: None,
+            store: None,
+            safety_identifier: None,
+            prompt_cache_key: None,
+            user: None,
+        }
+    }
+}
+
+impl Response {
+    /// Parse a response body, strings are sliced out of `body` instead of copied wherever serde could borrow them
+    pub fn from_body(body: &Str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str::<Response<Cow<'_, str>>>(body)
+            .map(|borrowed| borrowed.convert_to_owned(body))
+    }
+}
+
+/// A tool the response was allowed to call, as echoed back in `tools`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseTool<T = Str> {
+    Function(FunctionToolDefinition<T>),
+    WebSearch(WebSearchToolDefinition<T>),
+    /// See [`OutputItem::Unknown`]
+    #[serde(
+        untagged,
+        serialize_with = "serialize_unknown",
+        bound(serialize = "T: Deref<Target = str>")
+    )]
+    Unknown {
+        object_type: T,
+        raw: T,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionToolDefinition<T = Str> {
+    pub name: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<T>,
+    /// The JSON schema of the arguments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebSearchToolDefinition<T = Str> {
+    /// `low`, `medium` or `high`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_context_size: Option<T>,
+}
+
+/// Either a plain mode like `"auto"` or an object naming a specific tool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ResponseToolChoice<T = Str> {
+    Mode(ToolChoiceMode),
+    Tool {
+        #[serde(rename = "type")]
+        tool_type: T,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        name: Option<T>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "T: Serialize + Deref<Target = str>"))]
+pub struct ResponseTextConfig<T = Str> {
+    pub format: TextFormat<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verbosity: Option<T>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TextFormat<T = Str> {
+    Text,
+    JsonObject,
+    JsonSchema(JsonSchemaFormat<T>),
+    /// See [`OutputItem::Unknown`]
+    #[serde(
+        untagged,
+        serialize_with = "serialize_unknown",
+        bound(serialize = "T: Deref<Target = str>")
+    )]
+    Unknown {
+        object_type: T,
+        raw: T,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonSchemaFormat<T = Str> {
+    pub name: T,
+    pub schema: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseReasoning<T = Str> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effort: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<T>,
+    /// OpenRouter only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+impl_tagged_deserialize!(ResponseTool, Unknown { object_type, raw } [
+    "function" => Function,
+    "web_search" => WebSearch,
+]);
+
+impl_tagged_deserialize!(TextFormat, Unknown { object_type, raw } [
+    "json_schema" => JsonSchema,
+] [
+    "text" => Text,
+    "json_object" => JsonObject,
+]);
+
+impl_conversion!(Response struct [
+    id, model, output, output_text, error, incomplete_details, tools, tool_choice, text, reasoning,
+    previous_response_id, safety_identifier, prompt_cache_key, user
+] [
+    status, created_at, completed_at, usage, instructions, parallel_tool_calls, temperature, top_p,
+    presence_penalty, frequency_penalty, top_logprobs, max_output_tokens, max_tool_calls, metadata,
+    background, service_tier, truncation, store
+]);
+impl_conversion!(ResponseTool enum [Function, WebSearch] [] {Unknown [object_type, raw]});
+impl_conversion!(FunctionToolDefinition struct [name, description] [parameters, strict]);
+impl_conversion!(WebSearchToolDefinition struct [search_context_size] []);
+impl_conversion!(ResponseToolChoice enum [Mode] [] {Tool [tool_type, name]});
+impl_conversion!(ResponseTextConfig struct [format, verbosity] []);
+impl_conversion!(TextFormat enum [JsonSchema] [Text, JsonObject] {Unknown [object_type, raw]});
+impl_conversion!(JsonSchemaFormat struct [name, description] [schema, strict]);
+impl_conversion!(ResponseReasoning struct [effort, summary] [enabled]);
+impl_conversion!(ToolChoiceMode);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::samples;
+
+    const SAMPLES: &[&str] = &[
+        "anthropic_claude-sonnet-4.5",
+        "google_gemini-3-pro-preview",
+        "openai_gpt-5.2",
+        "z-ai_glm-4.7-flash",
+    ];
+
+    #[test]
+    fn test_samples_embed_full_response() {
+        for model in SAMPLES {
+            for sample in ["sample1_representative", "sample2_web_search"] {
+                let path = format!("{}/{}", model, sample);
+                let response = samples::completed_response(model, sample);
+                assert_eq!(response.status, ResponseStatus::Completed, "{}", path);
+                assert_eq!(
+                    response.model.as_deref(),
+                    Some(model.replacen('_', "/", 1).as_str())
+                );
+                assert!(response.created_at.is_some(), "{}", path);
+                assert!(response.completed_at.is_some(), "{}", path);
+                assert!(!response.output.is_empty(), "{}", path);
+                let usage = response.usage.unwrap();
+                assert!(usage.input_tokens_details.is_some(), "{}", path);
+                assert!(usage.output_tokens_details.is_some(), "{}", path);
+                assert!(usage.reasoning_tokens() <= usage.output_tokens, "{}", path);
+                assert_eq!(
+                    response.tool_choice,
+                    Some(ResponseToolChoice::Mode(ToolChoiceMode::Auto))
+                );
+                assert_eq!(response.truncation, Some(Truncation::Disabled));
+                assert!(matches!(
+                    response.text,
+                    Some(ResponseTextConfig {
+                        format: TextFormat::Text,
+                        ..
+                    })
+                ));
+
+                if sample == "sample1_representative" {
+                    assert!(
+                        response.tools.iter().all(|tool| matches!(
+                            tool,
+                            ResponseTool::Function(FunctionToolDefinition {
+                                parameters: Some(_),
+                                ..
+                            })
+                        )),
+                        "{}",
+                        path
+                    );
+                    assert_eq!(
+                        response.reasoning.and_then(|r| r.effort).as_deref(),
+                        Some("medium")
+                    );
+                } else {
+                    assert!(matches!(response.tools[..], [ResponseTool::WebSearch(_)]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_non_streaming_body() {
+        let body = Str::from(
+            r#"{"id":"resp_1","object":"response","created_at":1769599723,"completed_at":1769599725,"status":"completed","model":"openai/gpt-5.2","output":[{"type":"message","id":"msg_1","status":"completed","role":"assistant","content":[{"type":"output_text","text":"{\"ok\":true}","annotations":[]}]}],"tools":[{"type":"code_interpreter","container":{"type":"auto"}}],"tool_choice":{"type":"function","name":"lookup"},"text":{"format":{"type":"json_schema","name":"result","schema":{"type":"object"},"strict":true}},"metadata":{"job":"nightly"},"service_tier":"flex","usage":{"input_tokens":8,"output_tokens":3,"total_tokens":11}}"#,
+        );
+        let response = Response::from_body(&body).unwrap();
+
+        assert_eq!(&*response.id, "resp_1");
+        assert_eq!(response.completed_at, Some(1769599725));
+        assert_eq!(response.service_tier, Some(ServiceTier::Flex));
+        assert_eq!(response.metadata.unwrap()["job"], "nightly");
+        assert!(matches!(
+            &response.tools[..],
+            [ResponseTool::Unknown { object_type, .. }] if &**object_type == "code_interpreter"
+        ));
+        assert!(matches!(
+            response.tool_choice,
+            Some(ResponseToolChoice::Tool { ref tool_type, name: Some(ref name) })
+                if &**tool_type == "function" && &**name == "lookup"
+        ));
+        let Some(ResponseTextConfig {
+            format: TextFormat::JsonSchema(format),
+            ..
+        }) = response.text
+        else {
+            panic!("Expected a json_schema text format");
+        };
+        assert_eq!(&*format.name, "result");
+        assert_eq!(format.strict, Some(true));
+
+        // Unknown tools are written back out verbatim
+        let json = serde_json::to_value(&response.tools).unwrap();
+        assert_eq!(json[0]["container"]["type"], "auto");
+    }
+}
@@ -0,0 +1,716 @@
+//! Folds [`StreamEvent`]s back into the response they describe, so consumers don't each need their own delta merging state machine
+//!
+//! Deltas are applied as they arrive and the `*.done` events only overwrite with the value the deltas should already have built,
+//! so the snapshot comes out the same for providers that skip them.
+
+use std::{
+    collections::BTreeMap,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::Stream;
+
+use crate::openai_compat::endpoint::responses::{
+    response::Response,
+    stream::stream_item::{
+        Annotation, ContentPart, ConvertToString, FunctionCallItem, IncompleteDetails, ItemStatus,
+        MessageItem, OutputItem, OutputTextPart, ReasoningItem, ReasoningTextPart, ResponseError,
+        ResponseStatus, StreamEvent, SummaryContent, SummaryPart, SummaryTextPart, UrlCitation,
+        UsageInfo, WebSearchCallEventData, WebSearchCallItem,
+    },
+};
+
+/// The response as it stands after the events seen so far
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResponseSnapshot {
+    pub id: Option<String>,
+    pub status: Option<ResponseStatus>,
+    /// Output items keyed and ordered by their `output_index`
+    pub output: BTreeMap<u32, OutputItem<String>>,
+    pub usage: Option<UsageInfo>,
+    /// Set from `response.failed` or a bare `error` event
+    pub error: Option<ResponseError<String>>,
+    pub incomplete_details: Option<IncompleteDetails<String>>,
+}
+
+impl ResponseSnapshot {
+    /// All `output_text` of all messages, concatenated in output order
+    pub fn text(&self) -> String {
+        self.output_text_parts()
+            .map(|part| part.text.as_str())
+            .collect()
+    }
+
+    pub fn citations(&self) -> impl Iterator<Item = &UrlCitation<String>> {
+        self.output_text_parts()
+            .flat_map(|part| part.annotations.iter())
+            .filter_map(|annotation| match annotation {
+                Annotation::UrlCitation(citation) => Some(citation),
+                Annotation::Unknown { .. } => None,
+            })
+    }
+
+    /// A call whose `response.output_item.added` hasn't arrived yet has an empty `call_id` and `name`
+    pub fn function_calls(&self) -> impl Iterator<Item = &FunctionCallItem<String>> {
+        self.output.values().filter_map(|item| match item {
+            OutputItem::FunctionCall(call) => Some(call),
+            _ => None,
+        })
+    }
+
+    pub fn web_search_calls(&self) -> impl Iterator<Item = &WebSearchCallItem<String>> {
+        self.output.values().filter_map(|item| match item {
+            OutputItem::WebSearchCall(call) => Some(call),
+            _ => None,
+        })
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.status == Some(ResponseStatus::Completed)
+    }
+
+    fn output_text_parts(&self) -> impl Iterator<Item = &OutputTextPart<String>> {
+        self.output
+            .values()
+            .filter_map(|item| match item {
+                OutputItem::Message(message) => Some(message.content.iter()),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|part| match part {
+                ContentPart::OutputText(part) => Some(part),
+                _ => None,
+            })
+    }
+}
+
+/// Builds a [`ResponseSnapshot`] out of [`StreamEvent`]s, see [`AccumulatingStream`] to drive one from a stream directly
+#[derive(Debug, Clone, Default)]
+pub struct ResponseAccumulator {
+    snapshot: ResponseSnapshot,
+}
+
+impl ResponseAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn snapshot(&self) -> &ResponseSnapshot {
+        &self.snapshot
+    }
+
+    pub fn into_snapshot(self) -> ResponseSnapshot {
+        self.snapshot
+    }
+
+    /// Apply a single event and return the updated snapshot
+    pub fn push(&mut self, event: &StreamEvent) -> &ResponseSnapshot {
+        match event {
+            StreamEvent::ResponseCreated(data) => self.apply_metadata(&data.response),
+            StreamEvent::ResponseInProgress(data) => {
+                if let Some(response) = &data.response {
+                    self.apply_metadata(response)
+                }
+            }
+            StreamEvent::ResponseOutputItemAdded(data) => {
+                let added = data.item.clone().convert_to_string();
+                match self.snapshot.output.get_mut(&data.output_index) {
+                    Some(existing) => merge_added_item(existing, added),
+                    None => {
+                        self.snapshot.output.insert(data.output_index, added);
+                    }
+                }
+            }
+            StreamEvent::ResponseOutputItemDone(data) => {
+                let done = data.item.clone().convert_to_string();
+                match self.snapshot.output.get_mut(&data.output_index) {
+                    Some(existing) => merge_done_item(existing, done),
+                    None => {
+                        self.snapshot.output.insert(data.output_index, done);
+                    }
+                }
+            }
+            StreamEvent::ResponseContentPartAdded(data) => {
+                let item = self.item_mut(data.output_index, &data.item_id, &data.part);
+                if let Some(content) = item_content_mut(item) {
+                    let part = data.part.clone().convert_to_string();
+                    ensure_index(content, data.content_index as usize, || part.clone());
+                }
+            }
+            StreamEvent::ResponseContentPartDone(data) => {
+                let item = self.item_mut(data.output_index, &data.item_id, &data.part);
+                if let Some(content) = item_content_mut(item) {
+                    let part = data.part.clone().convert_to_string();
+                    let slot = ensure_index(content, data.content_index as usize, || part.clone());
+                    *slot = part;
+                }
+            }
+            StreamEvent::ResponseOutputTextDelta(data) => {
+                if let Some(part) =
+                    self.output_text_mut(data.output_index, &data.item_id, data.content_index)
+                {
+                    part.text.push_str(&data.delta);
+                }
+            }
+            StreamEvent::ResponseOutputTextDone(data) => {
+                if let Some(part) =
+                    self.output_text_mut(data.output_index, &data.item_id, data.content_index)
+                {
+                    part.text.replace_range(.., &data.text);
+                }
+            }
+            StreamEvent::ResponseOutputTextAnnotationAdded(data) => {
+                if let Some(part) =
+                    self.output_text_mut(data.output_index, &data.item_id, data.content_index)
+                {
+                    let annotation = data.annotation.clone().convert_to_string();
+                    let idx = data.annotation_index as usize;
+                    match part.annotations.get_mut(idx) {
+                        Some(existing) => *existing = annotation,
+                        None => part.annotations.push(annotation),
+                    }
+                }
+            }
+            StreamEvent::ResponseFunctionCallArgumentsDelta(data) => {
+                if let Some(call) = self.function_call_mut(data.output_index, &data.item_id) {
+                    call.arguments.push_str(&data.delta);
+                }
+            }
+            StreamEvent::ResponseFunctionCallArgumentsDone(data) => {
+                if let Some(call) = self.function_call_mut(data.output_index, &data.item_id) {
+                    call.arguments.replace_range(.., &data.arguments);
+                    call.name.replace_range(.., &data.name);
+                }
+            }
+            StreamEvent::ResponseReasoningTextDelta(data) => {
+                if let Some(part) =
+                    self.reasoning_text_mut(data.output_index, &data.item_id, data.content_index)
+                {
+                    part.text.push_str(&data.delta);
+                }
+            }
+            StreamEvent::ResponseReasoningTextDone(data) => {
+                if let Some(part) =
+                    self.reasoning_text_mut(data.output_index, &data.item_id, data.content_index)
+                {
+                    part.text.replace_range(.., &data.text);
+                }
+            }
+            StreamEvent::ResponseReasoningSummaryPartAdded(data) => {
+                if let Some(reasoning) = self.reasoning_mut(data.output_index, &data.item_id) {
+                    let part = data.part.clone().convert_to_string();
+                    ensure_index(&mut reasoning.summary, data.summary_index as usize, || {
+                        part.clone()
+                    });
+                }
+            }
+            StreamEvent::ResponseReasoningSummaryPartDone(data) => {
+                if let Some(reasoning) = self.reasoning_mut(data.output_index, &data.item_id) {
+                    let part = data.part.clone().convert_to_string();
+                    let slot =
+                        ensure_index(&mut reasoning.summary, data.summary_index as usize, || {
+                            part.clone()
+                        });
+                    *slot = part;
+                }
+            }
+            StreamEvent::ResponseReasoningSummaryTextDelta(data) => {
+                if let Some(part) =
+                    self.summary_text_mut(data.output_index, &data.item_id, data.summary_index)
+                {
+                    part.text.push_str(&data.delta);
+                }
+            }
+            StreamEvent::ResponseReasoningSummaryTextDone(data) => {
+                if let Some(part) =
+                    self.summary_text_mut(data.output_index, &data.item_id, data.summary_index)
+                {
+                    part.text.replace_range(.., &data.text);
+                }
+            }
+            StreamEvent::ResponseWebSearchCallInProgress(data) => {
+                self.set_web_search_status(data, ItemStatus::InProgress)
+            }
+            StreamEvent::ResponseWebSearchCallSearching(data) => {
+                self.set_web_search_status(data, ItemStatus::Searching)
+            }
+            StreamEvent::ResponseWebSearchCallCompleted(data) => {
+                self.set_web_search_status(data, ItemStatus::Completed)
+            }
+            StreamEvent::ResponseCompleted(data) => {
+                self.apply_metadata(&data.response);
+                // Without the item done events nothing else would ever mark the items as finished
+                for item in self.snapshot.output.values_mut() {
+                    match item {
+                        OutputItem::Message(MessageItem { status, .. })
+                        | OutputItem::FunctionCall(FunctionCallItem { status, .. })
+                        | OutputItem::WebSearchCall(WebSearchCallItem { status, .. })
+                            if matches!(status, ItemStatus::InProgress | ItemStatus::Searching) =>
+                        {
+                            *status = ItemStatus::Completed
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            StreamEvent::ResponseFailed(data) => self.apply_metadata(&data.response),
+            StreamEvent::ResponseIncomplete(data) => self.apply_metadata(&data.response),
+            StreamEvent::Error(data) => {
+                self.snapshot.error = Some(ResponseError {
+                    code: data.code.as_deref().unwrap_or_default().to_string(),
+                    message: data.message.to_string(),
+                });
+            }
+            // nothing to apply, the raw event is still available to whoever is consuming the stream
+            StreamEvent::Unknown { .. } => {}
+        }
+
+        &self.snapshot
+    }
+
+    fn apply_metadata(&mut self, response: &Response) {
+        self.snapshot.id = Some(response.id.to_string());
+        self.snapshot.status = Some(response.status);
+        if let Some(usage) = &response.usage {
+            self.snapshot.usage = Some(usage.clone());
+        }
+        if let Some(error) = &response.error {
+            self.snapshot.error = Some(error.clone().convert_to_string());
+        }
+        if let Some(details) = &response.incomplete_details {
+            self.snapshot.incomplete_details = Some(details.clone().convert_to_string());
+        }
+    }
+
+    /// Get the item at `output_index`, making a placeholder of the right kind for `part` if the provider never announced it
+    fn item_mut(
+        &mut self,
+        output_index: u32,
+        item_id: &str,
+        part: &ContentPart,
+    ) -> &mut OutputItem<String> {
+        self.snapshot
+            .output
+            .entry(output_index)
+            .or_insert_with(|| match part {
+                ContentPart::OutputText(_) | ContentPart::Unknown { .. } => {
+                    placeholder_message(item_id)
+                }
+                ContentPart::ReasoningText(_) | ContentPart::SummaryText(_) => {
+                    placeholder_reasoning(item_id)
+                }
+            })
+    }
+
+    fn output_text_mut(
+        &mut self,
+        output_index: u32,
+        item_id: &str,
+        content_index: u32,
+    ) -> Option<&mut OutputTextPart<String>> {
+        let item = self
+            .snapshot
+            .output
+            .entry(output_index)
+            .or_insert_with(|| placeholder_message(item_id));
+        let OutputItem::Message(message) = item else {
+            return None;
+        };
+        let part = ensure_index(&mut message.content, content_index as usize, || {
+            ContentPart::OutputText(OutputTextPart {
+                text: String::new(),
+                annotations: Vec::new(),
+            })
+        });
+        match part {
+            ContentPart::OutputText(part) => Some(part),
+            _ => None,
+        }
+    }
+
+    fn reasoning_mut(
+        &mut self,
+        output_index: u32,
+        item_id: &str,
+    ) -> Option<&mut ReasoningItem<String>> {
+        let item = self
+            .snapshot
+            .output
+            .entry(output_index)
+            .or_insert_with(|| placeholder_reasoning(item_id));
+        match item {
+            OutputItem::Reasoning(reasoning) => Some(reasoning),
+            _ => None,
+        }
+    }
+
+    fn set_web_search_status(&mut self, data: &WebSearchCallEventData, status: ItemStatus) {
+        let item = self
+            .snapshot
+            .output
+            .entry(data.output_index)
+            .or_insert_with(|| {
+                OutputItem::WebSearchCall(WebSearchCallItem {
+                    id: data.item_id.to_string(),
+                    status,
+                    action: None,
+                })
+            });
+        if let OutputItem::WebSearchCall(call) = item {
+            call.status = status;
+        }
+    }
+
+    fn reasoning_text_mut(
+        &mut self,
+        output_index: u32,
+        item_id: &str,
+        content_index: u32,
+    ) -> Option<&mut ReasoningTextPart<String>> {
+        let reasoning = self.reasoning_mut(output_index, item_id)?;
+        let part = ensure_index(&mut reasoning.content, content_index as usize, || {
+            ContentPart::ReasoningText(ReasoningTextPart {
+                text: String::new(),
+            })
+        });
+        match part {
+            ContentPart::ReasoningText(part) => Some(part),
+            _ => None,
+        }
+    }
+
+    fn summary_text_mut(
+        &mut self,
+        output_index: u32,
+        item_id: &str,
+        summary_index: u32,
+    ) -> Option<&mut SummaryTextPart<String>> {
+        let reasoning = self.reasoning_mut(output_index, item_id)?;
+        let part = ensure_index(&mut reasoning.summary, summary_index as usize, || {
+            SummaryPart {
+                content: SummaryContent::SummaryText(SummaryTextPart {
+                    text: String::new(),
+                }),
+            }
+        });
+        let SummaryContent::SummaryText(part) = &mut part.content;
+        Some(part)
+    }
+
+    fn function_call_mut(
+        &mut self,
+        output_index: u32,
+        item_id: &str,
+    ) -> Option<&mut FunctionCallItem<String>> {
+        // Only the item id is known from a delta, `call_id` and `name` stay empty until the added or done item fills them in
+        let item = self.snapshot.output.entry(output_index).or_insert_with(|| {
+            OutputItem::FunctionCall(FunctionCallItem {
+                id: Some(item_id.to_string()),
+                call_id: String::new(),
+                name: String::new(),
+                arguments: String::new(),
+                status: ItemStatus::InProgress,
+            })
+        });
+        match item {
+            OutputItem::FunctionCall(call) => Some(call),
+            _ => None,
+        }
+    }
+}
+
+fn placeholder_message(item_id: &str) -> OutputItem<String> {
+    OutputItem::Message(MessageItem {
+        id: item_id.to_string(),
+        status: ItemStatus::InProgress,
+        content: Vec::new(),
+    })
+}
+
+fn placeholder_reasoning(item_id: &str) -> OutputItem<String> {
+    OutputItem::Reasoning(ReasoningItem {
+        id: item_id.to_string(),
+        summary: Vec::new(),
+        content: Vec::new(),
+        encrypted_content: None,
+    })
+}
+
+fn item_content_mut(item: &mut OutputItem<String>) -> Option<&mut Vec<ContentPart<String>>> {
+    match item {
+        OutputItem::Message(message) => Some(&mut message.content),
+        OutputItem::Reasoning(reasoning) => Some(&mut reasoning.content),
+        OutputItem::FunctionCall(_) | OutputItem::WebSearchCall(_) | OutputItem::Unknown { .. } => {
+            None
+        }
+    }
+}
+
+/// Get the element at `idx`, filling any gap up to it with `fill`
+fn ensure_index<T>(vec: &mut Vec<T>, idx: usize, mut fill: impl FnMut() -> T) -> &mut T {
+    while vec.len() <= idx {
+        vec.push(fill());
+    }
+    &mut vec[idx]
+}
+
+/// An added item arriving after deltas already made a placeholder for it, keep what the deltas built and take the ids from the item
+fn merge_added_item(existing: &mut OutputItem<String>, added: OutputItem<String>) {
+    if let (OutputItem::FunctionCall(previous), OutputItem::FunctionCall(call)) = (existing, added)
+    {
+        if previous.call_id.is_empty() {
+            previous.call_id = call.call_id;
+        }
+        if previous.name.is_empty() {
+            previous.name = call.name;
+        }
+    }
+}
+
+/// The done item is the provider's final word on the item, but some drop fields the added item had (function call ids)
+fn merge_done_item(existing: &mut OutputItem<String>, mut done: OutputItem<String>) {
+    match (&mut *existing, &mut done) {
+        (OutputItem::FunctionCall(previous), OutputItem::FunctionCall(call)) => {
+            call.id = call.id.take().or(previous.id.take());
+        }
+        (OutputItem::WebSearchCall(previous), OutputItem::WebSearchCall(call)) => {
+            call.action = call.action.take().or(previous.action.take());
+        }
+        _ => {}
+    }
+
+    *existing = done;
+}
+
+pin_project_lite::pin_project! {
+    /// Passes events through untouched while keeping a [`ResponseAccumulator`] up to date with them
+    #[derive(Debug)]
+    pub struct AccumulatingStream<S> {
+        #[pin]
+        stream: S,
+        accumulator: ResponseAccumulator,
+    }
+}
+
+impl<S> AccumulatingStream<S> {
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            accumulator: ResponseAccumulator::new(),
+        }
+    }
+
+    /// The snapshot including every event yielded so far
+    pub fn snapshot(&self) -> &ResponseSnapshot {
+        self.accumulator.snapshot()
+    }
+
+    pub fn into_snapshot(self) -> ResponseSnapshot {
+        self.accumulator.into_snapshot()
+    }
+}
+
+impl<S, E> Stream for AccumulatingStream<S>
+where
+    S: Stream<Item = Result<StreamEvent, E>>,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let item = futures::ready!(this.stream.poll_next(cx));
+        if let Some(Ok(event)) = &item {
+            this.accumulator.push(event);
+        }
+        Poll::Ready(item)
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "miri"))] // These tests don't work with miri
+mod tests {
+    use std::convert::Infallible;
+
+    use bytes::Bytes;
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::{openai_compat::endpoint::responses::stream::OAICompatResponsesStream, samples};
+
+    async fn sample_events(model: &str, sample: &str) -> Vec<StreamEvent> {
+        samples::stream(model, sample)
+            .map(Result::unwrap)
+            .collect()
+            .await
+    }
+
+    fn is_done_event(event: &StreamEvent) -> bool {
+        matches!(
+            event,
+            StreamEvent::ResponseOutputItemDone(_)
+                | StreamEvent::ResponseContentPartDone(_)
+                | StreamEvent::ResponseOutputTextDone(_)
+                | StreamEvent::ResponseFunctionCallArgumentsDone(_)
+                | StreamEvent::ResponseReasoningTextDone(_)
+                | StreamEvent::ResponseReasoningSummaryPartDone(_)
+                | StreamEvent::ResponseReasoningSummaryTextDone(_)
+        )
+    }
+
+    fn fold<'a>(events: impl IntoIterator<Item = &'a StreamEvent>) -> ResponseSnapshot {
+        let mut accumulator = ResponseAccumulator::new();
+        for event in events {
+            accumulator.push(event);
+        }
+        accumulator.into_snapshot()
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_same_without_done_events() {
+        for model in [
+            "anthropic_claude-sonnet-4.5",
+            "google_gemini-3-pro-preview",
+            "openai_gpt-5.2",
+            "z-ai_glm-4.7-flash",
+        ] {
+            for sample in [
+                "sample1_representative",
+                "sample2_web_search",
+                "sample3_rejected",
+            ] {
+                let events = sample_events(model, sample).await;
+
+                let mut with_done = fold(&events);
+                let without_done = fold(events.iter().filter(|event| !is_done_event(event)));
+
+                // Gemini only sends its citations in the done events, there's nothing to rebuild them from without those
+                if !events
+                    .iter()
+                    .any(|event| matches!(event, StreamEvent::ResponseOutputTextAnnotationAdded(_)))
+                {
+                    for item in with_done.output.values_mut() {
+                        if let OutputItem::Message(message) = item {
+                            for part in &mut message.content {
+                                if let ContentPart::OutputText(part) = part {
+                                    part.annotations.clear();
+                                }
+                            }
+                        }
+                    }
+                }
+
+                assert_eq!(with_done, without_done, "{model}/{sample}");
+                assert!(with_done.is_completed(), "{model}/{sample}");
+                assert!(with_done.usage.is_some(), "{model}/{sample}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_matches_done_items() {
+        let events = sample_events("openai_gpt-5.2", "sample1_representative").await;
+        let snapshot = fold(&events);
+
+        let calls = snapshot.function_calls().collect::<Vec<_>>();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[0].name, "get_current_weather");
+        assert_eq!(
+            calls[0].arguments,
+            r#"{"location":"Tokyo, Japan","unit":"celsius"}"#
+        );
+
+        let events = sample_events("anthropic_claude-sonnet-4.5", "sample2_web_search").await;
+        let snapshot = fold(&events);
+
+        let done_text = events
+            .iter()
+            .find_map(|event| match event {
+                StreamEvent::ResponseOutputTextDone(data) => Some(data.text.to_string()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(snapshot.text(), done_text);
+        assert!(snapshot.citations().count() > 0);
+    }
+
+    #[tokio::test]
+    async fn test_accumulating_stream_tracks_events() {
+        let content = r#"data: {"type":"response.created","response":{"id":"test","status":"in_progress"},"sequence_number":0}
+
+data: {"type":"response.output_text.delta","output_index":0,"item_id":"msg","content_index":0,"delta":"Hel","sequence_number":1}
+
+data: {"type":"response.output_text.delta","output_index":0,"item_id":"msg","content_index":0,"delta":"lo","sequence_number":2}
+
+data: [DONE]
+"#;
+        let byte_stream =
+            futures::stream::iter([Ok::<_, Infallible>(Bytes::from(content.as_bytes()))]);
+        let mut stream = AccumulatingStream::new(OAICompatResponsesStream::new(byte_stream));
+
+        stream.next().await.unwrap().unwrap();
+        assert_eq!(stream.snapshot().id.as_deref(), Some("test"));
+        assert_eq!(stream.snapshot().text(), "");
+
+        stream.next().await.unwrap().unwrap();
+        assert_eq!(stream.snapshot().text(), "Hel");
+
+        while stream.next().await.is_some() {}
+        assert_eq!(stream.into_snapshot().text(), "Hello");
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_tracks_web_search_calls() {
+        let content = r#"data: {"type":"response.output_item.added","output_index":0,"item":{"type":"web_search_call","id":"ws_1","status":"in_progress"},"sequence_number":0}
+
+data: {"type":"response.web_search_call.in_progress","output_index":0,"item_id":"ws_1","sequence_number":1}
+
+data: {"type":"response.web_search_call.searching","output_index":0,"item_id":"ws_1","sequence_number":2}
+
+data: {"type":"response.web_search_call.completed","output_index":0,"item_id":"ws_1","sequence_number":3}
+
+data: {"type":"response.output_item.done","output_index":0,"item":{"type":"web_search_call","id":"ws_1","status":"completed","action":{"type":"search","query":"rust 2024 edition","sources":[{"type":"url","url":"https://doc.rust-lang.org/edition-guide/"}]}},"sequence_number":4}
+
+data: [DONE]
+"#;
+        let byte_stream =
+            futures::stream::iter([Ok::<_, Infallible>(Bytes::from(content.as_bytes()))]);
+        let mut stream = AccumulatingStream::new(OAICompatResponsesStream::new(byte_stream));
+
+        stream.next().await.unwrap().unwrap();
+        stream.next().await.unwrap().unwrap();
+        stream.next().await.unwrap().unwrap();
+        let call = stream.snapshot().web_search_calls().next().unwrap();
+        assert_eq!(call.status, ItemStatus::Searching);
+        assert_eq!(call.query(), None);
+
+        while stream.next().await.is_some() {}
+        let snapshot = stream.into_snapshot();
+        let call = snapshot.web_search_calls().next().unwrap();
+        assert_eq!(call.status, ItemStatus::Completed);
+        assert_eq!(call.query().map(String::as_str), Some("rust 2024 edition"));
+    }
+
+    #[tokio::test]
+    async fn test_deltas_before_added_item_dont_invent_call_id() {
+        let content = r#"data: {"type":"response.function_call_arguments.delta","item_id":"fc_1","output_index":0,"delta":"{\"q\":","sequence_number":1}
+
+data: {"type":"response.output_item.added","output_index":0,"item":{"type":"function_call","id":"fc_1","call_id":"call_1","name":"search","arguments":"","status":"in_progress"},"sequence_number":2}
+
+"#;
+        let byte_stream =
+            futures::stream::iter([Ok::<_, Infallible>(Bytes::from(content.as_bytes()))]);
+        let mut stream = AccumulatingStream::new(OAICompatResponsesStream::new(byte_stream));
+
+        stream.next().await.unwrap().unwrap();
+        let call = stream.snapshot().function_calls().next().unwrap();
+        assert_eq!(call.call_id, "");
+        assert_eq!(call.arguments, r#"{"q":"#);
+
+        stream.next().await.unwrap().unwrap();
+        let call = stream.snapshot().function_calls().next().unwrap();
+        assert_eq!(call.call_id, "call_1");
+        assert_eq!(call.name, "search");
+        assert_eq!(call.arguments, r#"{"q":"#);
+    }
+}
@@ -0,0 +1,405 @@
+//! Cutting a stream off once it has used more than it's allowed to, so a model that won't stop reasoning can't run up the bill.
+//!
+//! Output tokens aren't reported until the response is done, so they're estimated from the text of the deltas as they arrive.
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use futures::Stream;
+
+use crate::openai_compat::endpoint::{
+    models::catalog::Pricing,
+    responses::stream::{OAICompatResponsesStreamError, stream_item::StreamEvent},
+};
+
+/// The usual rule of thumb for English text and code, it overestimates for most tokenizers which is the safe side here
+const CHARS_PER_TOKEN: u64 = 4;
+
+/// Limits for [`BudgetStream`], all unset by default. Every kind of delta counts, reasoning and function call arguments included.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Budget {
+    pub max_output_tokens: Option<u64>,
+    pub max_chars: Option<u64>,
+    /// From the first time the stream is polled
+    pub max_duration: Option<Duration>,
+    /// In USD, estimated with [`Pricing::cost`] from `input_tokens` and the estimated output tokens
+    pub max_cost: Option<f64>,
+    pub pricing: Pricing,
+    /// The prompt size to price, the stream only learns it once the response is done
+    pub input_tokens: u64,
+}
+
+impl Budget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_output_tokens(mut self, max_output_tokens: u64) -> Self {
+        self.max_output_tokens = Some(max_output_tokens);
+        self
+    }
+
+    pub fn with_max_chars(mut self, max_chars: u64) -> Self {
+        self.max_chars = Some(max_chars);
+        self
+    }
+
+    pub fn with_max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    /// `pricing` is usually the model's [`ModelInfo::pricing`](crate::openai_compat::endpoint::models::catalog::ModelInfo::pricing)
+    pub fn with_max_cost(mut self, max_cost: f64, pricing: Pricing) -> Self {
+        self.max_cost = Some(max_cost);
+        self.pricing = pricing;
+        self
+    }
+
+    pub fn with_input_tokens(mut self, input_tokens: u64) -> Self {
+        self.input_tokens = input_tokens;
+        self
+    }
+
+    /// The first limit `spent` is over, checked in field order
+    fn check(&self, spent: &BudgetSpent) -> Option<BudgetExceeded> {
+        if let Some(limit) = self.max_output_tokens
+            && spent.output_tokens > limit
+        {
+            return Some(BudgetExceeded::OutputTokens {
+                limit,
+                estimated: spent.output_tokens,
+            });
+        }
+        if let Some(limit) = self.max_chars
+            && spent.chars > limit
+        {
+            return Some(BudgetExceeded::Chars {
+                limit,
+                count: spent.chars,
+            });
+        }
+        if let Some(limit) = self.max_cost {
+            let estimated = self.pricing.cost(self.input_tokens, spent.output_tokens);
+            if estimated > limit {
+                return Some(BudgetExceeded::Cost { limit, estimated });
+            }
+        }
+        None
+    }
+}
+
+/// What a [`BudgetStream`] has counted so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BudgetSpent {
+    /// Estimated from `chars`
+    pub output_tokens: u64,
+    pub chars: u64,
+}
+
+impl BudgetSpent {
+    fn push(&mut self, event: &StreamEvent) {
+        let delta = match event {
+            StreamEvent::ResponseOutputTextDelta(data) => &data.delta,
+            StreamEvent::ResponseReasoningTextDelta(data) => &data.delta,
+            StreamEvent::ResponseReasoningSummaryTextDelta(data) => &data.delta,
+            StreamEvent::ResponseFunctionCallArgumentsDelta(data) => &data.delta,
+            _ => return,
+        };
+        self.chars += delta.chars().count() as u64;
+        self.output_tokens = self.chars.div_ceil(CHARS_PER_TOKEN);
+    }
+}
+
+/// Which limit a [`BudgetStream`] hit, yielded as [`OAICompatResponsesStreamError::BudgetExceeded`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BudgetExceeded {
+    OutputTokens { limit: u64, estimated: u64 },
+    Chars { limit: u64, count: u64 },
+    Duration { limit: Duration, elapsed: Duration },
+    Cost { limit: f64, estimated: f64 },
+}
+
+impl std::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetExceeded::OutputTokens { limit, estimated } => write!(
+                f,
+                "output token budget of {} exceeded, about {} used",
+                limit, estimated
+            ),
+            BudgetExceeded::Chars { limit, count } => {
+                write!(f, "character budget of {} exceeded, {} used", limit, count)
+            }
+            BudgetExceeded::Duration { limit, elapsed } => write!(
+                f,
+                "time budget of {:?} exceeded, {:?} elapsed",
+                limit, elapsed
+            ),
+            BudgetExceeded::Cost { limit, estimated } => write!(
+                f,
+                "cost budget of ${} exceeded, about ${} used",
+                limit, estimated
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+pin_project_lite::pin_project! {
+    /// Passes events through until a [`Budget`] limit is exceeded, then drops the inner stream, closing the connection,
+    /// and ends with a single [`OAICompatResponsesStreamError::BudgetExceeded`].
+    /// The event that went over the limit is still yielded before the error.
+    #[derive(Debug)]
+    pub struct BudgetStream<S> {
+        #[pin]
+        state: BudgetStreamState<S>,
+        // Boxed so the stream stays `Unpin` when `S` is
+        deadline: Option<Pin<Box<tokio::time::Sleep>>>,
+        budget: Budget,
+        spent: BudgetSpent,
+        started: Option<Instant>,
+    }
+}
+
+pin_project_lite::pin_project! {
+    #[derive(Debug)]
+    #[project = BudgetStreamStateProjection]
+    enum BudgetStreamState<S> {
+        Active {
+            #[pin]
+            stream: S
+        },
+        Exceeded {
+            exceeded: BudgetExceeded
+        },
+        Terminated
+    }
+}
+
+impl<S> BudgetStream<S> {
+    pub fn new(stream: S, budget: Budget) -> Self {
+        Self {
+            state: BudgetStreamState::Active { stream },
+            deadline: None,
+            budget,
+            spent: BudgetSpent::default(),
+            started: None,
+        }
+    }
+
+    pub fn budget(&self) -> &Budget {
+        &self.budget
+    }
+
+    pub fn spent(&self) -> &BudgetSpent {
+        &self.spent
+    }
+
+    /// Since the first poll
+    pub fn elapsed(&self) -> Duration {
+        self.started
+            .map_or(Duration::ZERO, |started| started.elapsed())
+    }
+}
+
+impl<S, E> Stream for BudgetStream<S>
+where
+    S: Stream<Item = Result<StreamEvent, OAICompatResponsesStreamError<E>>>,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        let stream = match this.state.as_mut().project() {
+            BudgetStreamStateProjection::Active { stream } => stream,
+            BudgetStreamStateProjection::Exceeded { exceeded } => {
+                let exceeded = *exceeded;
+                this.state.set(BudgetStreamState::Terminated);
+                return Poll::Ready(Some(Err(OAICompatResponsesStreamError::BudgetExceeded(
+                    exceeded,
+                ))));
+            }
+            BudgetStreamStateProjection::Terminated => return Poll::Ready(None),
+        };
+
+        // The timer needs a runtime, which is only guaranteed once something polls
+        let started = *this.started.get_or_insert_with(Instant::now);
+        if let Some(limit) = this.budget.max_duration {
+            let deadline = this.deadline.get_or_insert_with(|| {
+                Box::pin(tokio::time::sleep(limit.saturating_sub(started.elapsed())))
+            });
+            if deadline.as_mut().poll(cx).is_ready() {
+                this.state.set(BudgetStreamState::Terminated);
+                *this.deadline = None;
+                return Poll::Ready(Some(Err(OAICompatResponsesStreamError::BudgetExceeded(
+                    BudgetExceeded::Duration {
+                        limit,
+                        elapsed: started.elapsed(),
+                    },
+                ))));
+            }
+        }
+
+        let item = futures::ready!(stream.poll_next(cx));
+        match &item {
+            Some(Ok(event)) => {
+                this.spent.push(event);
+                if let Some(exceeded) = this.budget.check(this.spent) {
+                    this.state.set(BudgetStreamState::Exceeded { exceeded });
+                    *this.deadline = None;
+                }
+            }
+            Some(Err(_)) => {}
+            None => {
+                this.state.set(BudgetStreamState::Terminated);
+                *this.deadline = None;
+            }
+        }
+        Poll::Ready(item)
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "miri"))] // These tests don't work with miri
+mod tests {
+    use std::{convert::Infallible, sync::Arc};
+
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::samples;
+
+    #[tokio::test]
+    async fn test_unlimited_budget_passes_everything() {
+        let expected = samples::stream("z-ai_glm-4.7-flash", "sample3_rejected")
+            .count()
+            .await;
+
+        let mut stream = BudgetStream::new(
+            samples::stream("z-ai_glm-4.7-flash", "sample3_rejected"),
+            Budget::new(),
+        );
+        let mut events = 0;
+        while let Some(item) = stream.next().await {
+            item.unwrap();
+            events += 1;
+        }
+        assert_eq!(events, expected);
+        assert!(stream.spent().output_tokens > 0);
+        assert_eq!(
+            stream.spent().output_tokens,
+            stream.spent().chars.div_ceil(CHARS_PER_TOKEN)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_runaway_reasoning_is_cut_off() {
+        let mut stream = BudgetStream::new(
+            samples::stream("z-ai_glm-4.7-flash", "sample3_rejected"),
+            Budget::new().with_max_output_tokens(100),
+        );
+
+        let mut events = Vec::new();
+        let exceeded = loop {
+            match stream.next().await.unwrap() {
+                Ok(event) => events.push(event),
+                Err(OAICompatResponsesStreamError::BudgetExceeded(exceeded)) => break exceeded,
+                Err(err) => panic!("unexpected error: {}", err),
+            }
+        };
+        assert!(stream.next().await.is_none());
+
+        assert!(matches!(
+            exceeded,
+            BudgetExceeded::OutputTokens { limit: 100, estimated } if estimated > 100
+        ));
+        // Still reasoning when it was stopped
+        assert!(matches!(
+            events.last(),
+            Some(StreamEvent::ResponseReasoningTextDelta(_))
+        ));
+        assert!(
+            !events
+                .iter()
+                .any(|event| matches!(event, StreamEvent::ResponseCompleted(_)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cost_and_chars_limits() {
+        let pricing = Pricing {
+            prompt: 0.000001,
+            completion: 0.00001,
+            ..Pricing::default()
+        };
+        let stream = BudgetStream::new(
+            samples::stream("z-ai_glm-4.7-flash", "sample3_rejected"),
+            Budget::new()
+                .with_input_tokens(1000)
+                .with_max_cost(0.002, pricing),
+        );
+        let Some(Err(OAICompatResponsesStreamError::BudgetExceeded(BudgetExceeded::Cost {
+            limit,
+            estimated,
+        }))) = stream
+            .filter(|item| futures::future::ready(item.is_err()))
+            .next()
+            .await
+        else {
+            panic!("expected the cost budget to trip");
+        };
+        assert_eq!(limit, 0.002);
+        // 0.001 for the input leaves room for 100 output tokens
+        assert!(estimated > 0.002 && estimated <= 0.002 + 0.00001);
+
+        let exceeded = BudgetStream::new(
+            samples::stream("z-ai_glm-4.7-flash", "sample3_rejected"),
+            Budget::new()
+                .with_max_chars(50)
+                .with_max_output_tokens(1000),
+        )
+        .filter_map(|item| futures::future::ready(item.err()))
+        .next()
+        .await;
+        assert!(matches!(
+            exceeded,
+            Some(OAICompatResponsesStreamError::BudgetExceeded(
+                BudgetExceeded::Chars { limit: 50, .. }
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_stalled_stream_times_out_and_drops_connection() {
+        let connection = Arc::new(());
+        let held = connection.clone();
+        let stalled = futures::stream::pending::<
+            Result<StreamEvent, OAICompatResponsesStreamError<Infallible>>,
+        >()
+        .map(move |item| {
+            let _ = &held;
+            item
+        });
+
+        let mut stream = BudgetStream::new(
+            stalled,
+            Budget::new().with_max_duration(Duration::from_millis(20)),
+        );
+        let Some(Err(OAICompatResponsesStreamError::BudgetExceeded(BudgetExceeded::Duration {
+            limit,
+            elapsed,
+        }))) = stream.next().await
+        else {
+            panic!("expected the time budget to trip");
+        };
+        assert_eq!(limit, Duration::from_millis(20));
+        assert!(elapsed >= limit);
+        assert_eq!(Arc::strong_count(&connection), 1);
+        assert!(stream.next().await.is_none());
+    }
+}
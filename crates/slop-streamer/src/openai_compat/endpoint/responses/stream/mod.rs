@@ -3,14 +3,24 @@ use std::{
     pin::Pin,
     str::Utf8Error,
     task::{Context, Poll},
+    time::Duration,
 };
 
+use bytes_utils::Str;
 use futures::Stream;
 
 use sseer::errors::EventStreamError;
 
-use crate::openai_compat::endpoint::responses::stream::stream_item::{ConvertToOwned, StreamEvent};
+use crate::openai_compat::{
+    api_error::ApiError,
+    endpoint::responses::stream::stream_item::{
+        ConvertToOwned, ErrorData, ResponseFailedData, StreamEvent,
+    },
+};
 
+pub mod accumulator;
+pub mod budget;
+pub mod partial_json;
 pub mod stream_item;
 
 pin_project_lite::pin_project! {
@@ -19,10 +29,22 @@ pin_project_lite::pin_project! {
     #[derive(Debug)]
     pub struct OAICompatResponsesStream<S> {
         #[pin]
-        state: OAICompatResponsesStreamState<S>
+        state: OAICompatResponsesStreamState<S>,
+        failures_as_errors: bool,
+        unknown_events: UnknownEventHandling,
     }
 }
 
+/// What [`OAICompatResponsesStream`] does with a `type` it doesn't recognise, be it an event or an item, content part or annotation inside one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum UnknownEventHandling {
+    /// Yield the event as is, unknown parts of it become the `Unknown` variant holding the raw JSON
+    #[default]
+    Lenient,
+    /// Yield [`OAICompatResponsesStreamError::UnknownType`] instead
+    Strict,
+}
+
 impl<S> OAICompatResponsesStream<S> {
     pub fn new(stream: S) -> Self
     where
@@ -32,8 +54,23 @@ impl<S> OAICompatResponsesStream<S> {
             state: OAICompatResponsesStreamState::Active {
                 stream: sseer::EventStream::new(stream),
             },
+            failures_as_errors: false,
+            unknown_events: UnknownEventHandling::default(),
         }
     }
+
+    /// Yield `response.failed` and `error` events as [`OAICompatResponsesStreamError::ResponseFailed`] and [`OAICompatResponsesStreamError::Event`] instead of as [`StreamEvent`]s.
+    /// The stream ends after either since the provider won't be sending anything else.
+    pub fn with_failures_as_errors(mut self, failures_as_errors: bool) -> Self {
+        self.failures_as_errors = failures_as_errors;
+        self
+    }
+
+    /// Defaults to [`UnknownEventHandling::Lenient`]
+    pub fn with_unknown_event_handling(mut self, unknown_events: UnknownEventHandling) -> Self {
+        self.unknown_events = unknown_events;
+        self
+    }
 }
 
 pin_project_lite::pin_project! {
@@ -53,6 +90,71 @@ pub enum OAICompatResponsesStreamError<E> {
     Transport(E),
     Utf8Error(Utf8Error),
     Deserialize(serde_json::Error),
+    /// The provider sent `response.failed`, only produced when [`OAICompatResponsesStream::with_failures_as_errors`] is set
+    ResponseFailed(Box<ResponseFailedData>),
+    /// The provider sent a bare `error` event, only produced when [`OAICompatResponsesStream::with_failures_as_errors`] is set
+    Event(ErrorData),
+    /// The provider sent an `{"error": {...}}` envelope as an event instead of an `error` event, which OpenRouter does when the upstream fails mid-stream.
+    /// The stream ends after it.
+    Api(ApiError),
+    /// Something in the event had a `type` this crate doesn't model, only produced with [`UnknownEventHandling::Strict`]
+    UnknownType(Str),
+    /// Every attempt failed before an event was received, oldest first, only produced by `ResponsesClient::stream_with_retry`
+    /// with the `client` feature
+    RetriesExhausted(Vec<FailedAttempt<E>>),
+    /// A limit was exceeded and the stream was cut off, only produced by [`budget::BudgetStream`]
+    BudgetExceeded(budget::BudgetExceeded),
+}
+
+/// One attempt that failed before anything was handed to the caller
+#[derive(Debug)]
+pub struct FailedAttempt<E> {
+    pub error: E,
+    /// Time since the first attempt was started
+    pub elapsed: Duration,
+    /// What the server asked for, if it did
+    pub retry_after: Option<Duration>,
+}
+
+impl<E> OAICompatResponsesStreamError<E> {
+    pub fn map_transport<F>(self, f: impl Fn(E) -> F) -> OAICompatResponsesStreamError<F> {
+        match self {
+            OAICompatResponsesStreamError::Transport(e) => {
+                OAICompatResponsesStreamError::Transport(f(e))
+            }
+            OAICompatResponsesStreamError::Utf8Error(e) => {
+                OAICompatResponsesStreamError::Utf8Error(e)
+            }
+            OAICompatResponsesStreamError::Deserialize(e) => {
+                OAICompatResponsesStreamError::Deserialize(e)
+            }
+            OAICompatResponsesStreamError::ResponseFailed(data) => {
+                OAICompatResponsesStreamError::ResponseFailed(data)
+            }
+            OAICompatResponsesStreamError::Event(data) => {
+                OAICompatResponsesStreamError::Event(data)
+            }
+            OAICompatResponsesStreamError::Api(error) => OAICompatResponsesStreamError::Api(error),
+            OAICompatResponsesStreamError::UnknownType(ty) => {
+                OAICompatResponsesStreamError::UnknownType(ty)
+            }
+            OAICompatResponsesStreamError::RetriesExhausted(attempts) => {
+                OAICompatResponsesStreamError::RetriesExhausted(
+                    attempts
+                        .into_iter()
+                        .map(|attempt| FailedAttempt {
+                            error: f(attempt.error),
+                            elapsed: attempt.elapsed,
+                            retry_after: attempt.retry_after,
+                        })
+                        .collect(),
+                )
+            }
+            OAICompatResponsesStreamError::BudgetExceeded(exceeded) => {
+                OAICompatResponsesStreamError::BudgetExceeded(exceeded)
+            }
+        }
+    }
 }
 
 impl<E> From<serde_json::Error> for OAICompatResponsesStreamError<E> {
@@ -79,6 +181,26 @@ where
             OAICompatResponsesStreamError::Transport(e) => e.fmt(f),
             OAICompatResponsesStreamError::Utf8Error(utf8_error) => utf8_error.fmt(f),
             OAICompatResponsesStreamError::Deserialize(error) => error.fmt(f),
+            OAICompatResponsesStreamError::ResponseFailed(data) => match &data.response.error {
+                Some(error) => write!(f, "response failed: {}: {}", error.code, error.message),
+                None => "response failed".fmt(f),
+            },
+            OAICompatResponsesStreamError::Event(data) => match &data.code {
+                Some(code) => write!(f, "{}: {}", code, data.message),
+                None => data.message.fmt(f),
+            },
+            OAICompatResponsesStreamError::Api(error) => error.fmt(f),
+            OAICompatResponsesStreamError::UnknownType(ty) => write!(f, "unknown type `{}`", ty),
+            OAICompatResponsesStreamError::RetriesExhausted(attempts) => match attempts.last() {
+                Some(last) => write!(
+                    f,
+                    "gave up after {} attempts: {}",
+                    attempts.len(),
+                    last.error
+                ),
+                None => "gave up without attempting".fmt(f),
+            },
+            OAICompatResponsesStreamError::BudgetExceeded(exceeded) => exceeded.fmt(f),
         }
     }
 }
@@ -115,11 +237,40 @@ where
             return Poll::Ready(None);
         }
 
-        Poll::Ready(Some(
-            serde_json::from_str::<StreamEvent<Cow<'_, str>>>(&ev.data)
-                .map(|borrowed| borrowed.convert_to_owned(&ev.data))
-                .map_err(From::from),
-        ))
+        let event = match serde_json::from_str::<StreamEvent<Cow<'_, str>>>(&ev.data) {
+            Ok(borrowed) => borrowed.convert_to_owned(&ev.data),
+            Err(err) => {
+                return Poll::Ready(Some(Err(match ApiError::from_body(ev.data.as_bytes()) {
+                    Some(error) => {
+                        this.state.set(OAICompatResponsesStreamState::Terminated);
+                        OAICompatResponsesStreamError::Api(error)
+                    }
+                    None => err.into(),
+                })));
+            }
+        };
+
+        if *this.unknown_events == UnknownEventHandling::Strict
+            && let Some(ty) = event.unknown_type()
+        {
+            return Poll::Ready(Some(Err(OAICompatResponsesStreamError::UnknownType(
+                ty.clone(),
+            ))));
+        }
+
+        if *this.failures_as_errors {
+            let err = match event {
+                StreamEvent::ResponseFailed(data) => {
+                    OAICompatResponsesStreamError::ResponseFailed(Box::new(data))
+                }
+                StreamEvent::Error(data) => OAICompatResponsesStreamError::Event(data),
+                event => return Poll::Ready(Some(Ok(event))),
+            };
+            this.state.set(OAICompatResponsesStreamState::Terminated);
+            return Poll::Ready(Some(Err(err)));
+        }
+
+        Poll::Ready(Some(Ok(event)))
     }
 }
 
@@ -127,6 +278,7 @@ where
 #[cfg(not(feature = "miri"))] // These tests don't work with miri
 mod tests {
     use super::*;
+    use crate::openai_compat::api_error::ApiErrorKind;
     use bytes::Bytes;
     use futures::StreamExt;
     use std::{convert::Infallible, path::PathBuf};
@@ -285,6 +437,278 @@ data: [DONE]
         assert!(result.is_none(), "Should immediately terminate on [DONE]");
     }
 
+    #[tokio::test]
+    async fn test_stream_error_envelope() {
+        let content = r#"data: {"type":"response.created","response":{"id":"test","status":"in_progress"},"sequence_number":0}
+
+data: {"error":{"code":502,"message":"Provider returned error","metadata":{"provider_name":"Anthropic","raw":"overloaded"}}}
+
+data: [DONE]
+"#;
+
+        let byte_stream = create_test_stream(content.to_string());
+        let events = OAICompatResponsesStream::new(byte_stream)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(events.len(), 2);
+        match &events[1] {
+            Err(OAICompatResponsesStreamError::Api(error)) => {
+                assert_eq!(error.kind(), ApiErrorKind::UpstreamUnavailable);
+                assert_eq!(
+                    error.metadata.as_ref().unwrap().provider_name.as_deref(),
+                    Some("Anthropic")
+                );
+            }
+            other => panic!("Expected an api error, got {:?}", other),
+        }
+    }
+
+    const FAILED_STREAM: &str = r#"data: {"type":"response.created","response":{"id":"test","status":"in_progress","error":null,"incomplete_details":null},"sequence_number":0}
+
+data: {"type":"response.failed","response":{"id":"test","status":"failed","error":{"code":"server_error","message":"upstream went away"},"incomplete_details":null},"sequence_number":1}
+
+data: [DONE]
+"#;
+
+    #[tokio::test]
+    async fn test_stream_parses_failure_events() {
+        let content = r#"data: {"type":"response.incomplete","response":{"id":"test","status":"incomplete","incomplete_details":{"reason":"max_output_tokens"}},"sequence_number":0}
+
+data: {"type":"error","code":"rate_limit_exceeded","message":"slow down","param":null,"sequence_number":1}
+
+data: [DONE]
+"#;
+
+        let byte_stream = create_test_stream(content.to_string());
+        let events = OAICompatResponsesStream::new(byte_stream)
+            .collect::<Vec<_>>()
+            .await;
+
+        match &events[0] {
+            Ok(StreamEvent::ResponseIncomplete(data)) => {
+                assert_eq!(
+                    data.response.status,
+                    stream_item::ResponseStatus::Incomplete
+                );
+                assert_eq!(
+                    &*data.response.incomplete_details.as_ref().unwrap().reason,
+                    "max_output_tokens"
+                );
+            }
+            other => panic!("Expected response.incomplete, got {:?}", other),
+        }
+
+        match &events[1] {
+            Ok(StreamEvent::Error(data)) => {
+                assert_eq!(data.code.as_deref(), Some("rate_limit_exceeded"));
+                assert_eq!(&*data.message, "slow down");
+            }
+            other => panic!("Expected error, got {:?}", other),
+        }
+
+        let byte_stream = create_test_stream(FAILED_STREAM.to_string());
+        let events = OAICompatResponsesStream::new(byte_stream)
+            .collect::<Vec<_>>()
+            .await;
+        assert!(matches!(events[1], Ok(StreamEvent::ResponseFailed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_stream_failures_as_errors() {
+        let byte_stream = create_test_stream(FAILED_STREAM.to_string());
+        let mut stream = OAICompatResponsesStream::new(byte_stream).with_failures_as_errors(true);
+
+        assert!(matches!(
+            stream.next().await,
+            Some(Ok(StreamEvent::ResponseCreated(_)))
+        ));
+
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "response failed: server_error: upstream went away"
+        );
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stream_parses_web_search_calls() {
+        let content = r#"data: {"type":"response.web_search_call.searching","output_index":0,"item_id":"ws_1","sequence_number":0}
+
+data: {"type":"response.output_item.done","output_index":0,"item":{"type":"web_search_call","id":"ws_1","status":"completed","action":{"type":"open_page","url":"https://example.com/"}},"sequence_number":1}
+
+data: [DONE]
+"#;
+
+        let byte_stream = create_test_stream(content.to_string());
+        let events = OAICompatResponsesStream::new(byte_stream)
+            .collect::<Vec<_>>()
+            .await;
+
+        match &events[0] {
+            Ok(StreamEvent::ResponseWebSearchCallSearching(data)) => {
+                assert_eq!(&*data.item_id, "ws_1");
+            }
+            other => panic!("Expected web search progress, got {:?}", other),
+        }
+
+        let item = match &events[1] {
+            Ok(StreamEvent::ResponseOutputItemDone(data)) => &data.item,
+            other => panic!("Expected output item done, got {:?}", other),
+        };
+        let stream_item::OutputItem::WebSearchCall(call) = item else {
+            panic!("Expected web search call, got {:?}", item);
+        };
+        assert!(matches!(
+            &call.action,
+            Some(stream_item::WebSearchAction::OpenPage(page))
+                if page.url.as_deref() == Some("https://example.com/")
+        ));
+        assert_eq!(
+            serde_json::to_string(item).unwrap(),
+            r#"{"type":"web_search_call","id":"ws_1","status":"completed","action":{"type":"open_page","url":"https://example.com/"}}"#
+        );
+    }
+
+    const UNKNOWN_STREAM: &str = r#"data: {"type":"response.audio.delta","item_id":"audio_1","delta":"AAAA","sequence_number":0}
+
+data: {"type":"response.output_item.added","output_index":0,"item":{"type": "image_generation_call", "status":"in_progress","id":"ig_1"},"sequence_number":1}
+
+data: {"type":"response.output_item.added","output_index":1,"item":{"type":"message","id":"msg_1","role":"assistant","status":"in_progress","content":[{"type":"output_text","text":"","annotations":[{"type":"file_citation","file_id":"file_1","index":0}]}]},"sequence_number":2}
+
+data: [DONE]
+"#;
+
+    #[tokio::test]
+    async fn test_stream_passes_unknown_types_through() {
+        let byte_stream = create_test_stream(UNKNOWN_STREAM.to_string());
+        let events = OAICompatResponsesStream::new(byte_stream)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(events.len(), 3);
+
+        match &events[0] {
+            Ok(StreamEvent::Unknown { event_type, raw }) => {
+                assert_eq!(&**event_type, "response.audio.delta");
+                assert_eq!(
+                    &**raw,
+                    r#"{"type":"response.audio.delta","item_id":"audio_1","delta":"AAAA","sequence_number":0}"#
+                );
+                // serializes back out as the original event rather than as a wrapper
+                let json = serde_json::to_value(events[0].as_ref().unwrap()).unwrap();
+                assert_eq!(json["delta"], "AAAA");
+            }
+            other => panic!("Expected unknown event, got {:?}", other),
+        }
+
+        match &events[1] {
+            Ok(StreamEvent::ResponseOutputItemAdded(data)) => {
+                let stream_item::OutputItem::Unknown { object_type, raw } = &data.item else {
+                    panic!("Expected unknown item, got {:?}", data.item);
+                };
+                assert_eq!(&**object_type, "image_generation_call");
+                // the bytes as sent, spacing and key order included, rather than re-serialized
+                assert_eq!(
+                    &**raw,
+                    r#"{"type": "image_generation_call", "status":"in_progress","id":"ig_1"}"#
+                );
+            }
+            other => panic!("Expected output item added, got {:?}", other),
+        }
+
+        let event = events[2].as_ref().unwrap();
+        assert_eq!(event.unknown_type().map(|ty| &**ty), Some("file_citation"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_strict_unknown_types() {
+        let byte_stream = create_test_stream(UNKNOWN_STREAM.to_string());
+        let events = OAICompatResponsesStream::new(byte_stream)
+            .with_unknown_event_handling(UnknownEventHandling::Strict)
+            .collect::<Vec<_>>()
+            .await;
+
+        let errors = events
+            .iter()
+            .map(|event| match event {
+                Err(OAICompatResponsesStreamError::UnknownType(ty)) => ty.to_string(),
+                other => panic!("Expected unknown type error, got {:?}", other),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                "response.audio.delta",
+                "image_generation_call",
+                "file_citation"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_still_rejects_malformed_known_events() {
+        let content = r#"data: {"type":"response.output_text.delta","item_id":"msg_1","sequence_number":0}
+
+data: {"item_id":"msg_1","sequence_number":1}
+
+data: [DONE]
+"#;
+
+        let byte_stream = create_test_stream(content.to_string());
+        let events = OAICompatResponsesStream::new(byte_stream)
+            .collect::<Vec<_>>()
+            .await;
+        assert!(matches!(
+            events[0],
+            Err(OAICompatResponsesStreamError::Deserialize(_))
+        ));
+        assert!(matches!(
+            events[1],
+            Err(OAICompatResponsesStreamError::Deserialize(_))
+        ));
+    }
+
+    #[test]
+    fn test_tagged_types_deserialize_without_borrowing() {
+        let events = [
+            serde_json::json!({"type":"response.output_text.delta","output_index":0,"item_id":"msg_1","content_index":0,"delta":"Hi","sequence_number":3}),
+            serde_json::json!({"type":"response.output_item.added","output_index":1,"item":{"type":"function_call","call_id":"call_1","name":"get_weather","arguments":"","status":"in_progress"},"sequence_number":4}),
+            serde_json::json!({"type":"response.output_item.added","output_index":2,"item":{"type":"image_generation_call","status":"in_progress","id":"ig_1"},"sequence_number":5}),
+            serde_json::json!({"type":"response.audio.delta","item_id":"audio_1","delta":"AAAA","sequence_number":6}),
+        ];
+
+        for json in events {
+            let from_value = serde_json::from_value::<StreamEvent<String>>(json.clone()).unwrap();
+            let from_reader =
+                serde_json::from_reader::<_, StreamEvent<String>>(json.to_string().as_bytes())
+                    .unwrap();
+            assert_eq!(from_value, from_reader);
+            assert_eq!(serde_json::to_value(&from_value).unwrap(), json);
+        }
+
+        let item = serde_json::json!({"type":"function_call","call_id":"call_1","name":"get_weather","arguments":"{}","status":"completed"});
+        let from_value =
+            serde_json::from_value::<stream_item::OutputItem<String>>(item.clone()).unwrap();
+        assert!(matches!(
+            from_value,
+            stream_item::OutputItem::FunctionCall(_)
+        ));
+        assert_eq!(serde_json::to_value(&from_value).unwrap(), item);
+
+        let item =
+            serde_json::json!({"type":"image_generation_call","status":"in_progress","id":"ig_1"});
+        let from_reader = serde_json::from_reader::<_, stream_item::OutputItem<String>>(
+            item.to_string().as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(
+            from_reader.unknown_type().map(String::as_str),
+            Some("image_generation_call")
+        );
+        assert_eq!(serde_json::to_value(&from_reader).unwrap(), item);
+    }
+
     #[tokio::test]
     async fn test_stream_skips_processing_lines() {
         let content = r#": OPENROUTER PROCESSING
@@ -0,0 +1,671 @@
+//! Best-effort parsing of JSON that is still streaming in, so function call arguments and structured output can be shown before their `.done` event
+//!
+//! The text is buffered as deltas arrive and parsed when a value is asked for, with open strings, arrays and objects closed
+//! and a trailing key or literal that hasn't finished left out. That's one pass over what has arrived so far per call,
+//! which is cheap at the sizes arguments and structured output come in.
+
+use std::{
+    collections::BTreeMap,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::Stream;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Number, Value};
+
+use crate::openai_compat::endpoint::responses::stream::stream_item::StreamEvent;
+
+/// One JSON document being streamed in
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PartialJson {
+    text: String,
+    done: bool,
+}
+
+impl PartialJson {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, fragment: &str) {
+        self.text.push_str(fragment);
+    }
+
+    /// Replace the buffered text with the final one, which the deltas should already add up to
+    pub fn finish(&mut self, text: &str) {
+        self.text.clear();
+        self.text.push_str(text);
+        self.done = true;
+    }
+
+    /// Everything received so far
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Whether the `.done` event has been seen
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// The value so far with open strings, arrays and objects closed. Unfinished keys, numbers and literals are left out.
+    /// `Ok(None)` until the first value has started.
+    pub fn value(&self) -> Result<Option<Value>, PartialJsonError> {
+        Parser::new(&self.text, false).parse()
+    }
+
+    /// Like [`value`](Self::value), but without strings and numbers that may still grow.
+    /// Arrays and objects are still included while open, with only their complete elements.
+    pub fn complete_value(&self) -> Result<Option<Value>, PartialJsonError> {
+        Parser::new(&self.text, true).parse()
+    }
+
+    /// [`complete_value`](Self::complete_value) as a `T`, `None` while that isn't possible yet.
+    /// Fields become available as they complete if `T` makes them `Option` or `#[serde(default)]`.
+    pub fn parse<T>(&self) -> Option<T>
+    where
+        T: DeserializeOwned,
+    {
+        T::deserialize(self.complete_value().ok()??).ok()
+    }
+}
+
+/// Arrays and objects nested deeper than this are an error rather than a stack overflow, the same limit as serde_json's
+pub const MAX_DEPTH: usize = 128;
+
+/// The text can't become valid JSON however it continues, or nests deeper than [`MAX_DEPTH`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartialJsonError {
+    /// Byte offset of the first invalid character, or of the bracket that went past [`MAX_DEPTH`]
+    pub position: usize,
+}
+
+impl std::fmt::Display for PartialJsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid JSON at byte {}", self.position)
+    }
+}
+
+impl std::error::Error for PartialJsonError {}
+
+enum Parsed {
+    Complete(Value),
+    /// Cut off by the end of the text
+    Partial(Value),
+    /// Cut off before there was anything to show
+    Missing,
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    /// Arrays and objects currently open
+    depth: usize,
+    complete_only: bool,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str, complete_only: bool) -> Self {
+        Self {
+            text,
+            pos: 0,
+            depth: 0,
+            complete_only,
+        }
+    }
+
+    fn parse(mut self) -> Result<Option<Value>, PartialJsonError> {
+        let value = match self.parse_value()? {
+            Parsed::Complete(value) | Parsed::Partial(value) => Some(value),
+            Parsed::Missing => None,
+        };
+        self.skip_whitespace();
+        match self.peek() {
+            None => Ok(value),
+            Some(_) => Err(self.error()),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn error(&self) -> PartialJsonError {
+        PartialJsonError { position: self.pos }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn partial(&self, value: Value) -> Parsed {
+        if self.complete_only {
+            Parsed::Missing
+        } else {
+            Parsed::Partial(value)
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Parsed, PartialJsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Ok(Parsed::Missing),
+            Some(b'{') => self.parse_nested(Self::parse_object),
+            Some(b'[') => self.parse_nested(Self::parse_array),
+            Some(b'"') => Ok(match self.parse_string()? {
+                (string, true) => Parsed::Complete(Value::String(string)),
+                (string, false) => self.partial(Value::String(string)),
+            }),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(b't') => self.parse_literal("true", Value::Bool(true)),
+            Some(b'f') => self.parse_literal("false", Value::Bool(false)),
+            Some(b'n') => self.parse_literal("null", Value::Null),
+            Some(_) => Err(self.error()),
+        }
+    }
+
+    fn parse_nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Parsed, PartialJsonError>,
+    ) -> Result<Parsed, PartialJsonError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error());
+        }
+        self.depth += 1;
+        let parsed = parse(self);
+        self.depth -= 1;
+        parsed
+    }
+
+    fn parse_object(&mut self) -> Result<Parsed, PartialJsonError> {
+        self.pos += 1;
+        let mut map = Map::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Parsed::Complete(Value::Object(map)));
+        }
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Ok(Parsed::Partial(Value::Object(map))),
+                Some(b'"') => {}
+                Some(_) => return Err(self.error()),
+            }
+            let (key, true) = self.parse_string()? else {
+                return Ok(Parsed::Partial(Value::Object(map)));
+            };
+
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Ok(Parsed::Partial(Value::Object(map))),
+                Some(b':') => self.pos += 1,
+                Some(_) => return Err(self.error()),
+            }
+
+            match self.parse_value()? {
+                Parsed::Complete(value) => {
+                    map.insert(key, value);
+                }
+                Parsed::Partial(value) => {
+                    map.insert(key, value);
+                    return Ok(Parsed::Partial(Value::Object(map)));
+                }
+                Parsed::Missing => return Ok(Parsed::Partial(Value::Object(map))),
+            }
+
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Ok(Parsed::Partial(Value::Object(map))),
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Parsed::Complete(Value::Object(map)));
+                }
+                Some(_) => return Err(self.error()),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Parsed, PartialJsonError> {
+        self.pos += 1;
+        let mut array = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Parsed::Complete(Value::Array(array)));
+        }
+
+        loop {
+            match self.parse_value()? {
+                Parsed::Complete(value) => array.push(value),
+                Parsed::Partial(value) => {
+                    array.push(value);
+                    return Ok(Parsed::Partial(Value::Array(array)));
+                }
+                Parsed::Missing => return Ok(Parsed::Partial(Value::Array(array))),
+            }
+
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Ok(Parsed::Partial(Value::Array(array))),
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Parsed::Complete(Value::Array(array)));
+                }
+                Some(_) => return Err(self.error()),
+            }
+        }
+    }
+
+    /// The string and whether its closing quote was reached. A trailing escape that's cut off is left out.
+    fn parse_string(&mut self) -> Result<(String, bool), PartialJsonError> {
+        self.pos += 1;
+        let mut string = String::new();
+
+        loop {
+            let rest = &self.text[self.pos..];
+            let Some(special) = rest.find(['"', '\\']) else {
+                string.push_str(rest);
+                self.pos = self.text.len();
+                return Ok((string, false));
+            };
+            string.push_str(&rest[..special]);
+            self.pos += special;
+
+            if self.peek() == Some(b'"') {
+                self.pos += 1;
+                return Ok((string, true));
+            }
+
+            let escape_start = self.pos;
+            self.pos += 1;
+            let Some(escape) = self.peek() else {
+                return Ok((string, false));
+            };
+            self.pos += 1;
+            let unescaped = match escape {
+                b'"' => '"',
+                b'\\' => '\\',
+                b'/' => '/',
+                b'b' => '\u{8}',
+                b'f' => '\u{c}',
+                b'n' => '\n',
+                b'r' => '\r',
+                b't' => '\t',
+                b'u' => match self.parse_unicode_escape()? {
+                    Some(c) => c,
+                    None => return Ok((string, false)),
+                },
+                _ => {
+                    self.pos = escape_start;
+                    return Err(self.error());
+                }
+            };
+            string.push(unescaped);
+        }
+    }
+
+    /// After the `\u`, `None` if cut off. Lone surrogates become U+FFFD rather than an error.
+    fn parse_unicode_escape(&mut self) -> Result<Option<char>, PartialJsonError> {
+        let Some(high) = self.parse_hex4()? else {
+            return Ok(None);
+        };
+        if !(0xD800..0xDC00).contains(&high) {
+            return Ok(Some(char::from_u32(high).unwrap_or('\u{FFFD}')));
+        }
+
+        let rest = &self.text.as_bytes()[self.pos..];
+        if rest.len() < 2 {
+            return Ok(if b"\\u".starts_with(rest) {
+                None
+            } else {
+                Some('\u{FFFD}')
+            });
+        }
+        if !rest.starts_with(b"\\u") {
+            return Ok(Some('\u{FFFD}'));
+        }
+        self.pos += 2;
+        let Some(low) = self.parse_hex4()? else {
+            return Ok(None);
+        };
+        if !(0xDC00..0xE000).contains(&low) {
+            return Ok(Some('\u{FFFD}'));
+        }
+        Ok(char::from_u32(
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00),
+        ))
+    }
+
+    fn parse_hex4(&mut self) -> Result<Option<u32>, PartialJsonError> {
+        let rest = &self.text.as_bytes()[self.pos..];
+        let digits = &rest[..rest.len().min(4)];
+        if let Some(invalid) = digits.iter().position(|b| !b.is_ascii_hexdigit()) {
+            self.pos += invalid;
+            return Err(self.error());
+        }
+        if digits.len() < 4 {
+            self.pos = self.text.len();
+            return Ok(None);
+        }
+        self.pos += 4;
+        // Four ASCII hex digits, so both of these succeed
+        Ok(std::str::from_utf8(digits)
+            .ok()
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok()))
+    }
+
+    /// A number cut off by the end of the text is shown up to its last digit, e.g. `-1.` as `-1`
+    fn parse_number(&mut self) -> Result<Parsed, PartialJsonError> {
+        let start = self.pos;
+        while matches!(
+            self.peek(),
+            Some(b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')
+        ) {
+            self.pos += 1;
+        }
+        let number = &self.text[start..self.pos];
+
+        if self.pos < self.text.len() {
+            return match serde_json::from_str::<Number>(number) {
+                Ok(number) => Ok(Parsed::Complete(Value::Number(number))),
+                Err(_) => {
+                    self.pos = start;
+                    Err(self.error())
+                }
+            };
+        }
+
+        let digits = number.trim_end_matches(['-', '+', '.', 'e', 'E']);
+        if digits.is_empty() {
+            return Ok(Parsed::Missing);
+        }
+        match serde_json::from_str::<Number>(digits) {
+            Ok(number) => Ok(self.partial(Value::Number(number))),
+            Err(_) => {
+                self.pos = start;
+                Err(self.error())
+            }
+        }
+    }
+
+    /// A literal cut off by the end of the text is left out, since there's no partial form to show
+    fn parse_literal(&mut self, literal: &str, value: Value) -> Result<Parsed, PartialJsonError> {
+        let rest = &self.text[self.pos..];
+        if rest.starts_with(literal) {
+            self.pos += literal.len();
+            Ok(Parsed::Complete(value))
+        } else if literal.starts_with(rest) {
+            self.pos = self.text.len();
+            Ok(Parsed::Missing)
+        } else {
+            Err(self.error())
+        }
+    }
+}
+
+/// A [`PartialJson`] per output item, fed by the `function_call_arguments` and `output_text` events.
+/// A message's text parts are treated as one document, structured output only ever comes as one part.
+#[derive(Debug, Clone, Default)]
+pub struct PartialJsonTracker {
+    items: BTreeMap<String, PartialJson>,
+}
+
+impl PartialJsonTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a single event, returning the item id and document it changed if any
+    pub fn push<'a>(&'a mut self, event: &'a StreamEvent) -> Option<(&'a str, &'a PartialJson)> {
+        let (item_id, text, done) = match event {
+            StreamEvent::ResponseFunctionCallArgumentsDelta(data) => {
+                (&data.item_id, &data.delta, false)
+            }
+            StreamEvent::ResponseFunctionCallArgumentsDone(data) => {
+                (&data.item_id, &data.arguments, true)
+            }
+            StreamEvent::ResponseOutputTextDelta(data) => (&data.item_id, &data.delta, false),
+            StreamEvent::ResponseOutputTextDone(data) => (&data.item_id, &data.text, true),
+            _ => return None,
+        };
+
+        if !self.items.contains_key(&**item_id) {
+            self.items.insert(item_id.to_string(), PartialJson::new());
+        }
+        let partial = self.items.get_mut(&**item_id)?;
+        if done {
+            partial.finish(text);
+        } else {
+            partial.push(text);
+        }
+        Some((item_id, partial))
+    }
+
+    pub fn get(&self, item_id: &str) -> Option<&PartialJson> {
+        self.items.get(item_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &PartialJson)> {
+        self.items
+            .iter()
+            .map(|(item_id, partial)| (item_id.as_str(), partial))
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// Passes events through unchanged while tracking the partial JSON of every item, like [`AccumulatingStream`](super::accumulator::AccumulatingStream)
+    pub struct PartialJsonStream<S> {
+        #[pin]
+        stream: S,
+        tracker: PartialJsonTracker,
+    }
+}
+
+impl<S> PartialJsonStream<S> {
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            tracker: PartialJsonTracker::new(),
+        }
+    }
+
+    /// Including every event yielded so far
+    pub fn tracker(&self) -> &PartialJsonTracker {
+        &self.tracker
+    }
+
+    pub fn get(&self, item_id: &str) -> Option<&PartialJson> {
+        self.tracker.get(item_id)
+    }
+
+    pub fn into_tracker(self) -> PartialJsonTracker {
+        self.tracker
+    }
+}
+
+impl<S, E> Stream for PartialJsonStream<S>
+where
+    S: Stream<Item = Result<StreamEvent, E>>,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let item = futures::ready!(this.stream.poll_next(cx));
+        if let Some(Ok(event)) = &item {
+            this.tracker.push(event);
+        }
+        Poll::Ready(item)
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "miri"))] // These tests don't work with miri
+mod tests {
+    use std::convert::Infallible;
+
+    use bytes::Bytes;
+    use futures::StreamExt;
+    use serde_json::json;
+
+    use super::*;
+    use crate::openai_compat::endpoint::responses::stream::OAICompatResponsesStream;
+
+    fn values(text: &str, complete_only: bool) -> Vec<Option<Value>> {
+        (1..=text.len())
+            .filter(|end| text.is_char_boundary(*end))
+            .map(|end| Parser::new(&text[..end], complete_only).parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_every_prefix_parses() {
+        let text = r#"{"city": "Zürich\n\"old town\"", "days": [1, -2.5e1, true, null], "nested": {"a": {}, "b": []}, "emoji": "😀"}"#;
+        let full = serde_json::from_str::<Value>(text).unwrap();
+
+        let best_effort = values(text, false);
+        assert_eq!(best_effort.last().unwrap().as_ref(), Some(&full));
+        assert_eq!(values(text, true).last().unwrap().as_ref(), Some(&full));
+
+        let at = |prefix: &str| Parser::new(prefix, false).parse().unwrap();
+        assert_eq!(at("  "), None);
+        assert_eq!(at(r#"{"ci"#), Some(json!({})));
+        assert_eq!(at(r#"{"city": "Zür"#), Some(json!({"city": "Zür"})));
+        assert_eq!(at(r#"{"city": "a\"#), Some(json!({"city": "a"})));
+        assert_eq!(at(r#"{"city": "\ud83d\ude"#), Some(json!({"city": ""})));
+        assert_eq!(at(r#"{"days": [1, -2."#), Some(json!({"days": [1, -2]})));
+        assert_eq!(at(r#"{"days": [1, tr"#), Some(json!({"days": [1]})));
+        assert_eq!(
+            at(r#"{"nested": {"a": {"#),
+            Some(json!({"nested": {"a": {}}}))
+        );
+
+        let complete = |prefix: &str| Parser::new(prefix, true).parse().unwrap();
+        assert_eq!(complete(r#"{"city": "Zür"#), Some(json!({})));
+        assert_eq!(
+            complete(r#"{"city": "Zürich", "days": [1, 2"#),
+            Some(json!({"city": "Zürich", "days": [1]}))
+        );
+    }
+
+    #[test]
+    fn test_invalid_json_is_an_error() {
+        for (text, position) in [
+            (r#"{"a" 1}"#, 5),
+            (r#"{"a": tru}"#, 6),
+            (r#"[1,,2]"#, 3),
+            (r#"{"a": "\x"}"#, 7),
+            (r#"{"a": 1} 2"#, 9),
+            (r#"{"a": 1-}"#, 6),
+        ] {
+            assert_eq!(
+                Parser::new(text, false).parse(),
+                Err(PartialJsonError { position }),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let deep = "[".repeat(100_000);
+        assert_eq!(
+            Parser::new(&deep, false).parse(),
+            Err(PartialJsonError {
+                position: MAX_DEPTH
+            })
+        );
+
+        let mut partial = PartialJson::new();
+        partial.push(&"[".repeat(MAX_DEPTH));
+        assert!(partial.value().unwrap().is_some());
+        partial.push("{");
+        assert!(partial.value().is_err());
+        assert_eq!(partial.parse::<Value>(), None);
+    }
+
+    #[test]
+    fn test_typed_view_fills_in() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Weather {
+            city: Option<String>,
+            #[serde(default)]
+            days: Vec<u32>,
+        }
+
+        let mut partial = PartialJson::new();
+        let mut seen = Vec::new();
+        for fragment in [
+            r#"{"ci"#,
+            r#"ty": "Zür"#,
+            r#"ich", "days": [3"#,
+            r#", 4]"#,
+            "}",
+        ] {
+            partial.push(fragment);
+            seen.push(partial.parse::<Weather>().unwrap());
+        }
+
+        assert_eq!(seen[1].city, None);
+        assert_eq!(seen[2].city.as_deref(), Some("Zürich"));
+        assert_eq!(seen[2].days, Vec::<u32>::new());
+        assert_eq!(seen[3].days, [3, 4]);
+        assert_eq!(seen[3], seen[4]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_tracks_items() {
+        let content = r#"data: {"type":"response.function_call_arguments.delta","item_id":"fc_1","output_index":0,"delta":"{\"expression\": \"2 +","sequence_number":1}
+
+data: {"type":"response.output_text.delta","output_index":1,"item_id":"msg_1","content_index":0,"delta":"{\"answer\": [","sequence_number":2}
+
+data: {"type":"response.function_call_arguments.delta","item_id":"fc_1","output_index":0,"delta":" 2\"}","sequence_number":3}
+
+data: {"type":"response.function_call_arguments.done","item_id":"fc_1","output_index":0,"name":"calculate","arguments":"{\"expression\": \"2 + 2\"}","sequence_number":4}
+
+data: [DONE]
+"#;
+        let byte_stream =
+            futures::stream::iter([Ok::<_, Infallible>(Bytes::from(content.as_bytes()))]);
+        let mut stream = PartialJsonStream::new(OAICompatResponsesStream::new(byte_stream));
+
+        stream.next().await.unwrap().unwrap();
+        assert_eq!(
+            stream.get("fc_1").unwrap().value().unwrap(),
+            Some(json!({"expression": "2 +"}))
+        );
+        assert!(stream.get("msg_1").is_none());
+
+        stream.next().await.unwrap().unwrap();
+        assert_eq!(
+            stream.get("msg_1").unwrap().value().unwrap(),
+            Some(json!({"answer": []}))
+        );
+
+        while stream.next().await.is_some() {}
+        let tracker = stream.into_tracker();
+        let arguments = tracker.get("fc_1").unwrap();
+        assert!(arguments.is_done());
+        assert_eq!(
+            arguments.value().unwrap(),
+            Some(json!({"expression": "2 + 2"}))
+        );
+        assert_eq!(
+            tracker
+                .iter()
+                .map(|(item_id, _)| item_id)
+                .collect::<Vec<_>>(),
+            ["fc_1", "msg_1"]
+        );
+    }
+}
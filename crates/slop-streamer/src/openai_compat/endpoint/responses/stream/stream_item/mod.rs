@@ -1,9 +1,11 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, ops::Deref};
 
 use bytes::Bytes;
 use bytes_utils::Str;
 use serde::{Deserialize, Serialize};
 
+use crate::openai_compat::endpoint::responses::response::Response;
+
 const_str!(pub struct ResponseStr("response"));
 const_str!(pub struct MessageStr("message"));
 const_str!(pub struct ReasoningStr("reasoning"));
@@ -12,6 +14,7 @@ const_str!(pub struct OutputTextStr("output_text"));
 const_str!(pub struct ReasoningTextStr("reasoning_text"));
 const_str!(pub struct SummaryTextStr("summary_text"));
 const_str!(pub struct UrlCitationStr("url_citation"));
+const_str!(pub struct WebSearchCallStr("web_search_call"));
 
 // Enums for fields with multiple discrete values
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -19,24 +22,34 @@ const_str!(pub struct UrlCitationStr("url_citation"));
 pub enum ResponseStatus {
     InProgress,
     Completed,
+    Failed,
+    Incomplete,
+    Cancelled,
+    Queued,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ItemStatus {
     InProgress,
+    /// Only used by [`WebSearchCallItem`]s
+    Searching,
     Completed,
+    Failed,
+    Incomplete,
+    Cancelled,
+    Queued,
 }
 
 // Main event types
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum StreamEvent<T = Str> {
     #[serde(rename = "response.created")]
-    ResponseCreated(ResponseCreatedData),
+    ResponseCreated(ResponseCreatedData<T>),
 
     #[serde(rename = "response.in_progress")]
-    ResponseInProgress(ResponseInProgressData),
+    ResponseInProgress(ResponseInProgressData<T>),
 
     #[serde(rename = "response.output_item.added")]
     ResponseOutputItemAdded(OutputItemAddedData<T>),
@@ -83,34 +96,128 @@ pub enum StreamEvent<T = Str> {
     #[serde(rename = "response.reasoning_summary_part.done")]
     ResponseReasoningSummaryPartDone(ReasoningSummaryPartDoneData<T>),
 
+    #[serde(rename = "response.web_search_call.in_progress")]
+    ResponseWebSearchCallInProgress(WebSearchCallEventData<T>),
+
+    #[serde(rename = "response.web_search_call.searching")]
+    ResponseWebSearchCallSearching(WebSearchCallEventData<T>),
+
+    #[serde(rename = "response.web_search_call.completed")]
+    ResponseWebSearchCallCompleted(WebSearchCallEventData<T>),
+
     #[serde(rename = "response.completed")]
-    ResponseCompleted(ResponseCompletedData),
+    ResponseCompleted(ResponseCompletedData<T>),
+
+    #[serde(rename = "response.failed")]
+    ResponseFailed(ResponseFailedData<T>),
+
+    #[serde(rename = "response.incomplete")]
+    ResponseIncomplete(ResponseIncompleteData<T>),
+
+    #[serde(rename = "error")]
+    Error(ErrorData<T>),
+
+    /// An event type this crate doesn't model (yet), `raw` is the whole event's JSON and is what gets serialized back out.
+    /// Only produced when the stream is lenient, see [`UnknownEventHandling`](super::UnknownEventHandling)
+    #[serde(
+        untagged,
+        serialize_with = "serialize_unknown",
+        bound(serialize = "T: Deref<Target = str>")
+    )]
+    Unknown { event_type: T, raw: T },
+}
+
+/// Writes `raw` out as the JSON it holds rather than as a string. `Deref` rather than `AsRef<str>` because [`Str`] only has `AsRef` for sized targets.
+pub(crate) fn serialize_unknown<T: Deref<Target = str>, S: serde::Serializer>(
+    _type: &T,
+    raw: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    use serde::ser::Error;
+
+    serde_json::from_str::<&serde_json::value::RawValue>(raw)
+        .map_err(S::Error::custom)?
+        .serialize(serializer)
+}
+
+impl<T> StreamEvent<T> {
+    /// The `type` of the first thing in this event that wasn't recognised, whether that's the event itself or an item, part or annotation inside it
+    pub fn unknown_type(&self) -> Option<&T> {
+        match self {
+            StreamEvent::Unknown { event_type, .. } => Some(event_type),
+            StreamEvent::ResponseOutputItemAdded(OutputItemAddedData { item, .. })
+            | StreamEvent::ResponseOutputItemDone(OutputItemDoneData { item, .. }) => {
+                item.unknown_type()
+            }
+            StreamEvent::ResponseContentPartAdded(ContentPartAddedData { part, .. })
+            | StreamEvent::ResponseContentPartDone(ContentPartDoneData { part, .. }) => {
+                part.unknown_type()
+            }
+            StreamEvent::ResponseOutputTextAnnotationAdded(data) => data.annotation.unknown_type(),
+            _ => None,
+        }
+    }
 }
 
 // Response lifecycle events
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct ResponseCreatedData {
-    pub response: ResponseMetadata,
+#[serde(bound(serialize = "T: Serialize + Deref<Target = str>"))]
+pub struct ResponseCreatedData<T = Str> {
+    pub response: Response<T>,
     pub sequence_number: u64,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct ResponseInProgressData {
+#[serde(bound(serialize = "T: Serialize + Deref<Target = str>"))]
+pub struct ResponseInProgressData<T = Str> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<Response<T>>,
     pub sequence_number: u64,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct ResponseCompletedData {
-    pub response: ResponseMetadata,
+#[serde(bound(serialize = "T: Serialize + Deref<Target = str>"))]
+pub struct ResponseCompletedData<T = Str> {
+    pub response: Response<T>,
     pub sequence_number: u64,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub struct ResponseMetadata<T = Str> {
-    pub id: T,
-    pub status: ResponseStatus,
+#[serde(bound(serialize = "T: Serialize + Deref<Target = str>"))]
+pub struct ResponseFailedData<T = Str> {
+    pub response: Response<T>,
+    pub sequence_number: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "T: Serialize + Deref<Target = str>"))]
+pub struct ResponseIncompleteData<T = Str> {
+    pub response: Response<T>,
+    pub sequence_number: u64,
+}
+
+/// The bare `error` event, sent instead of `response.failed` when there is no response to attach the error to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorData<T = Str> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<T>,
+    pub message: T,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub usage: Option<UsageInfo>,
+    pub param: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence_number: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseError<T = Str> {
+    pub code: T,
+    pub message: T,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IncompleteDetails<T = Str> {
+    /// Usually `max_output_tokens` or `content_filter`
+    pub reason: T,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -119,11 +226,60 @@ pub struct UsageInfo {
     pub output_tokens: u64,
     pub total_tokens: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_tokens_details: Option<InputTokensDetails>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_tokens_details: Option<OutputTokensDetails>,
+    /// OpenRouter only, in credits
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cost: Option<f64>,
+    /// OpenRouter only, whether the request used the caller's own provider key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_byok: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_details: Option<CostDetails>,
+}
+
+impl UsageInfo {
+    /// Input tokens read from the prompt cache, 0 if the provider didn't say
+    pub fn cached_tokens(&self) -> u64 {
+        self.input_tokens_details
+            .map_or(0, |details| details.cached_tokens)
+    }
+
+    /// The part of `output_tokens` spent reasoning, 0 if the provider didn't say
+    pub fn reasoning_tokens(&self) -> u64 {
+        self.output_tokens_details
+            .map_or(0, |details| details.reasoning_tokens)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputTokensDetails {
+    #[serde(default)]
+    pub cached_tokens: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputTokensDetails {
+    #[serde(default)]
+    pub reasoning_tokens: u64,
+}
+
+/// OpenRouter only, what the upstream provider charged, in USD. For BYOK requests this is billed to the key's owner
+/// and `cost` is only OpenRouter's fee.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream_inference_cost: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream_inference_input_cost: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream_inference_output_cost: Option<f64>,
 }
 
 // Output item events
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "T: Serialize + Deref<Target = str>"))]
 pub struct OutputItemAddedData<T = Str> {
     pub output_index: u32,
     pub item: OutputItem<T>,
@@ -131,21 +287,50 @@ pub struct OutputItemAddedData<T = Str> {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "T: Serialize + Deref<Target = str>"))]
 pub struct OutputItemDoneData<T = Str> {
     pub output_index: u32,
     pub item: OutputItem<T>,
     pub sequence_number: u64,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum OutputItem<T = Str> {
     Message(MessageItem<T>),
     Reasoning(ReasoningItem<T>),
     FunctionCall(FunctionCallItem<T>),
+    WebSearchCall(WebSearchCallItem<T>),
+    /// A `type` this crate doesn't model (yet), `raw` is the JSON object and is what gets serialized back out
+    #[serde(
+        untagged,
+        serialize_with = "serialize_unknown",
+        bound(serialize = "T: Deref<Target = str>")
+    )]
+    Unknown {
+        object_type: T,
+        raw: T,
+    },
+}
+
+impl<T> OutputItem<T> {
+    pub fn unknown_type(&self) -> Option<&T> {
+        match self {
+            OutputItem::Message(message) => {
+                message.content.iter().find_map(ContentPart::unknown_type)
+            }
+            OutputItem::Reasoning(reasoning) => {
+                reasoning.content.iter().find_map(ContentPart::unknown_type)
+            }
+            OutputItem::FunctionCall(_) => None,
+            OutputItem::WebSearchCall(call) => call.action.as_ref()?.unknown_type(),
+            OutputItem::Unknown { object_type, .. } => Some(object_type),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "T: Serialize + Deref<Target = str>"))]
 pub struct MessageItem<T = Str> {
     pub id: T,
     pub status: ItemStatus,
@@ -154,24 +339,114 @@ pub struct MessageItem<T = Str> {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "T: Serialize + Deref<Target = str>"))]
 pub struct ReasoningItem<T = Str> {
     pub id: T,
     #[serde(default = "Vec::new")]
     pub summary: Vec<SummaryPart<T>>,
+    #[serde(default = "Vec::new")]
+    pub content: Vec<ContentPart<T>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub encrypted_content: Option<T>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FunctionCallItem<T = Str> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<T>,
     pub call_id: T,
     pub name: T,
     pub arguments: T,
     pub status: ItemStatus,
 }
 
+/// A search the provider ran on the model's behalf, the progress of which is reported by the `response.web_search_call.*` events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "T: Serialize + Deref<Target = str>"))]
+pub struct WebSearchCallItem<T = Str> {
+    pub id: T,
+    pub status: ItemStatus,
+    /// Usually only filled in once the search is done
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<WebSearchAction<T>>,
+}
+
+impl<T> WebSearchCallItem<T> {
+    /// What was searched for, if this was a search and the provider said
+    pub fn query(&self) -> Option<&T> {
+        match &self.action {
+            Some(WebSearchAction::Search(search)) => search.query.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WebSearchAction<T = Str> {
+    Search(SearchAction<T>),
+    OpenPage(OpenPageAction<T>),
+    Find(FindAction<T>),
+    /// See [`OutputItem::Unknown`]
+    #[serde(
+        untagged,
+        serialize_with = "serialize_unknown",
+        bound(serialize = "T: Deref<Target = str>")
+    )]
+    Unknown {
+        object_type: T,
+        raw: T,
+    },
+}
+
+impl<T> WebSearchAction<T> {
+    pub fn unknown_type(&self) -> Option<&T> {
+        match self {
+            WebSearchAction::Unknown { object_type, .. } => Some(object_type),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchAction<T = Str> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<T>,
+    /// Only present when the sources were asked for with `include`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sources: Option<Vec<WebSearchSource<T>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenPageAction<T = Str> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<T>,
+}
+
+/// Searching within a page the model opened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FindAction<T = Str> {
+    pub url: T,
+    pub pattern: T,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WebSearchSource<T = Str> {
+    Url { url: T },
+}
+
+// Web search call events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebSearchCallEventData<T = Str> {
+    pub output_index: u32,
+    pub item_id: T,
+    pub sequence_number: u64,
+}
+
 // Content part events
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "T: Serialize + Deref<Target = str>"))]
 pub struct ContentPartAddedData<T = Str> {
     pub item_id: T,
     pub output_index: u32,
@@ -181,6 +456,7 @@ pub struct ContentPartAddedData<T = Str> {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "T: Serialize + Deref<Target = str>"))]
 pub struct ContentPartDoneData<T = Str> {
     pub item_id: T,
     pub output_index: u32,
@@ -189,15 +465,38 @@ pub struct ContentPartDoneData<T = Str> {
     pub sequence_number: u64,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ContentPart<T = Str> {
     OutputText(OutputTextPart<T>),
     ReasoningText(ReasoningTextPart<T>),
     SummaryText(SummaryTextPart<T>),
+    /// See [`OutputItem::Unknown`]
+    #[serde(
+        untagged,
+        serialize_with = "serialize_unknown",
+        bound(serialize = "T: Deref<Target = str>")
+    )]
+    Unknown {
+        object_type: T,
+        raw: T,
+    },
+}
+
+impl<T> ContentPart<T> {
+    pub fn unknown_type(&self) -> Option<&T> {
+        match self {
+            ContentPart::OutputText(part) => {
+                part.annotations.iter().find_map(Annotation::unknown_type)
+            }
+            ContentPart::ReasoningText(_) | ContentPart::SummaryText(_) => None,
+            ContentPart::Unknown { object_type, .. } => Some(object_type),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "T: Serialize + Deref<Target = str>"))]
 pub struct OutputTextPart<T = Str> {
     pub text: T,
     #[serde(default = "Vec::new")]
@@ -245,6 +544,19 @@ pub struct OutputTextDoneData<T = Str> {
     pub sequence_number: u64,
 }
 
+impl<T> OutputTextDoneData<T>
+where
+    T: std::ops::Deref<Target = str>,
+{
+    /// Parse the finished text as JSON, for responses asked for with a `json_schema` or `json_object` text format
+    pub fn parse_json<'a, U>(&'a self) -> Result<U, serde_json::Error>
+    where
+        U: Deserialize<'a>,
+    {
+        serde_json::from_str(&self.text)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ReasoningTextDeltaData<T = Str> {
     pub output_index: u32,
@@ -319,6 +631,7 @@ pub struct FunctionCallArgumentsDoneData<T = Str> {
 
 // Annotation events
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "T: Serialize + Deref<Target = str>"))]
 pub struct AnnotationAddedData<T = Str> {
     pub output_index: u32,
     pub item_id: T,
@@ -328,10 +641,29 @@ pub struct AnnotationAddedData<T = Str> {
     pub annotation: Annotation<T>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Annotation<T = Str> {
     UrlCitation(UrlCitation<T>),
+    /// See [`OutputItem::Unknown`]
+    #[serde(
+        untagged,
+        serialize_with = "serialize_unknown",
+        bound(serialize = "T: Deref<Target = str>")
+    )]
+    Unknown {
+        object_type: T,
+        raw: T,
+    },
+}
+
+impl<T> Annotation<T> {
+    pub fn unknown_type(&self) -> Option<&T> {
+        match self {
+            Annotation::UrlCitation(_) => None,
+            Annotation::Unknown { object_type, .. } => Some(object_type),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -342,6 +674,234 @@ pub struct UrlCitation<T = Str> {
     pub end_index: u32,
 }
 
+/// Deserialize for internally tagged enums that can't just derive it, an unrecognized `type` becomes the `Unknown` variant instead of an error.
+/// serde's own `#[serde(untagged)]` fallback would also swallow real errors in events we do know about, so the tags are routed explicitly here.
+/// Input borrowed by `serde_json::from_str` or `from_slice` stays borrowed, anything else (`from_value`, `from_reader`, other formats) goes through a [`RawJson::Owned`] copy.
+macro_rules! impl_tagged_deserialize {
+    ($target:ident, Unknown { $($field:ident),* } [$($tag:literal => $variant:ident),* $(,)?] $([$($unit_tag:literal => $unit_variant:ident),* $(,)?])?) => {
+        impl<'de, T> Deserialize<'de> for $target<T>
+        where
+            T: Deserialize<'de>,
+        {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                use serde::de::{
+                    Error, IntoDeserializer,
+                    value::{BorrowedStrDeserializer, StringDeserializer},
+                };
+
+                // Reading `type` and then the variant costs two scans of the raw JSON but no intermediate `Value` when it's
+                // borrowed, and leaves `raw` as the bytes that arrived for the fallback
+                let raw = $crate::openai_compat::endpoint::responses::stream::stream_item::RawJson::deserialize(deserializer)?;
+                let tag = raw.tag().map_err(D::Error::custom)?;
+
+                match &*tag {
+                    $(
+                        $tag => raw.parse().map(Self::$variant).map_err(D::Error::custom),
+                    )*
+                    $($(
+                        $unit_tag => Ok(Self::$unit_variant),
+                    )*)?
+                    _ => {
+                        let tag = match tag {
+                            std::borrow::Cow::Borrowed(tag) => T::deserialize(BorrowedStrDeserializer::<D::Error>::new(tag)),
+                            std::borrow::Cow::Owned(tag) => T::deserialize::<StringDeserializer<D::Error>>(tag.into_deserializer()),
+                        };
+                        let raw = match raw {
+                            $crate::openai_compat::endpoint::responses::stream::stream_item::RawJson::Borrowed(raw) => {
+                                T::deserialize(BorrowedStrDeserializer::<D::Error>::new(raw))
+                            }
+                            $crate::openai_compat::endpoint::responses::stream::stream_item::RawJson::Owned(raw) => {
+                                T::deserialize::<StringDeserializer<D::Error>>(raw.into_deserializer())
+                            }
+                        };
+                        let [$($field),*] = [tag, raw];
+                        Ok(Self::Unknown {
+                            $($field: $field?,)*
+                        })
+                    }
+                }
+            }
+        }
+    };
+}
+
+/// Just the `type` of a tagged object, borrowed unless it has escapes
+#[derive(Deserialize)]
+pub(crate) struct TypeTag<'a> {
+    #[serde(borrow, rename = "type")]
+    pub(crate) tag: Cow<'a, str>,
+}
+
+/// The JSON text of one tagged object, as [`impl_tagged_deserialize`] sees it
+pub(crate) enum RawJson<'de> {
+    /// Straight out of the input, when serde_json is reading from a borrowed `&str` or `&[u8]`
+    Borrowed(&'de str),
+    /// Anything that can't lend out its input, like `serde_json::from_value`, `from_reader` or another format altogether
+    Owned(String),
+}
+
+/// The name serde_json recognises in `deserialize_newtype_struct` to hand out the raw text of the next value, it's what
+/// [`RawValue`](serde_json::value::RawValue) deserializes through. `&RawValue` can't be used directly because it refuses owned text
+/// instead of falling back to it.
+const RAW_VALUE_TOKEN: &str = "$serde_json::private::RawValue";
+
+impl<'de> RawJson<'de> {
+    pub(crate) fn tag(&self) -> serde_json::Result<Cow<'de, str>> {
+        match self {
+            RawJson::Borrowed(raw) => serde_json::from_str::<TypeTag<'de>>(raw).map(|tag| tag.tag),
+            RawJson::Owned(raw) => {
+                serde_json::from_str::<TypeTag<'_>>(raw).map(|tag| Cow::Owned(tag.tag.into_owned()))
+            }
+        }
+    }
+
+    /// Deserialize a variant from the raw text, borrowing from the input where it can
+    pub(crate) fn parse<V: Deserialize<'de>>(self) -> serde_json::Result<V> {
+        match self {
+            RawJson::Borrowed(raw) => serde_json::from_str(raw),
+            RawJson::Owned(raw) => V::deserialize(serde_json::from_str::<serde_json::Value>(&raw)?),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RawJson<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(RAW_VALUE_TOKEN, RawJsonVisitor)
+    }
+}
+
+struct RawJsonVisitor;
+
+impl<'de> serde::de::Visitor<'de> for RawJsonVisitor {
+    type Value = RawJson<'de>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a JSON object")
+    }
+
+    // serde_json answers the token with a single entry map whose value is the raw text
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let Some(key) = map.next_key::<String>()? else {
+            return Ok(RawJson::Owned("{}".to_string()));
+        };
+        if key == RAW_VALUE_TOKEN {
+            return map.next_value_seed(RawTextSeed);
+        }
+
+        // A deserializer that doesn't know the token and just handed over the object, buffer it instead
+        let mut object = serde_json::Map::new();
+        object.insert(key, map.next_value()?);
+        while let Some((key, value)) = map.next_entry()? {
+            object.insert(key, value);
+        }
+        Ok(RawJson::Owned(
+            serde_json::Value::Object(object).to_string(),
+        ))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        serde_json::Value::deserialize(deserializer).map(|value| RawJson::Owned(value.to_string()))
+    }
+}
+
+/// The raw text serde_json hands over for [`RAW_VALUE_TOKEN`], borrowed when the input is
+struct RawTextSeed;
+
+impl<'de> serde::de::DeserializeSeed<'de> for RawTextSeed {
+    type Value = RawJson<'de>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de> serde::de::Visitor<'de> for RawTextSeed {
+    type Value = RawJson<'de>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("raw JSON text")
+    }
+
+    fn visit_borrowed_str<E: serde::de::Error>(self, raw: &'de str) -> Result<Self::Value, E> {
+        Ok(RawJson::Borrowed(raw))
+    }
+
+    fn visit_str<E: serde::de::Error>(self, raw: &str) -> Result<Self::Value, E> {
+        Ok(RawJson::Owned(raw.to_string()))
+    }
+
+    fn visit_string<E: serde::de::Error>(self, raw: String) -> Result<Self::Value, E> {
+        Ok(RawJson::Owned(raw))
+    }
+}
+
+pub(crate) use impl_tagged_deserialize;
+
+impl_tagged_deserialize!(StreamEvent, Unknown { event_type, raw } [
+    "response.created" => ResponseCreated,
+    "response.in_progress" => ResponseInProgress,
+    "response.output_item.added" => ResponseOutputItemAdded,
+    "response.content_part.added" => ResponseContentPartAdded,
+    "response.output_text.delta" => ResponseOutputTextDelta,
+    "response.output_text.annotation.added" => ResponseOutputTextAnnotationAdded,
+    "response.output_text.done" => ResponseOutputTextDone,
+    "response.content_part.done" => ResponseContentPartDone,
+    "response.output_item.done" => ResponseOutputItemDone,
+    "response.function_call_arguments.delta" => ResponseFunctionCallArgumentsDelta,
+    "response.function_call_arguments.done" => ResponseFunctionCallArgumentsDone,
+    "response.reasoning_text.delta" => ResponseReasoningTextDelta,
+    "response.reasoning_text.done" => ResponseReasoningTextDone,
+    "response.reasoning_summary_part.added" => ResponseReasoningSummaryPartAdded,
+    "response.reasoning_summary_text.delta" => ResponseReasoningSummaryTextDelta,
+    "response.reasoning_summary_text.done" => ResponseReasoningSummaryTextDone,
+    "response.reasoning_summary_part.done" => ResponseReasoningSummaryPartDone,
+    "response.web_search_call.in_progress" => ResponseWebSearchCallInProgress,
+    "response.web_search_call.searching" => ResponseWebSearchCallSearching,
+    "response.web_search_call.completed" => ResponseWebSearchCallCompleted,
+    "response.completed" => ResponseCompleted,
+    "response.failed" => ResponseFailed,
+    "response.incomplete" => ResponseIncomplete,
+    "error" => Error,
+]);
+
+impl_tagged_deserialize!(OutputItem, Unknown { object_type, raw } [
+    "message" => Message,
+    "reasoning" => Reasoning,
+    "function_call" => FunctionCall,
+    "web_search_call" => WebSearchCall,
+]);
+
+impl_tagged_deserialize!(WebSearchAction, Unknown { object_type, raw } [
+    "search" => Search,
+    "open_page" => OpenPage,
+    "find" => Find,
+]);
+
+impl_tagged_deserialize!(ContentPart, Unknown { object_type, raw } [
+    "output_text" => OutputText,
+    "reasoning_text" => ReasoningText,
+    "summary_text" => SummaryText,
+]);
+
+impl_tagged_deserialize!(Annotation, Unknown { object_type, raw } [
+    "url_citation" => UrlCitation,
+]);
+
 pub(crate) trait ConvertToOwned {
     type Owned;
     fn convert_to_owned(self, buf: &Str) -> Self::Owned;
@@ -364,6 +924,44 @@ impl ConvertToOwned for Cow<'_, str> {
     }
 }
 
+/// Like [`ConvertToOwned`] but into plain `String`s, for when the text needs to be appended to (accumulating deltas)
+pub(crate) trait ConvertToString {
+    type Owned;
+    fn convert_to_string(self) -> Self::Owned;
+}
+
+impl ConvertToString for Str {
+    type Owned = String;
+
+    fn convert_to_string(self) -> String {
+        String::from(&*self)
+    }
+}
+
+impl<T> ConvertToString for Vec<T>
+where
+    T: ConvertToString,
+{
+    type Owned = Vec<<T as ConvertToString>::Owned>;
+
+    fn convert_to_string(self) -> Self::Owned {
+        self.into_iter()
+            .map(ConvertToString::convert_to_string)
+            .collect()
+    }
+}
+
+impl<T> ConvertToString for Option<T>
+where
+    T: ConvertToString,
+{
+    type Owned = Option<<T as ConvertToString>::Owned>;
+
+    fn convert_to_string(self) -> Self::Owned {
+        self.map(ConvertToString::convert_to_string)
+    }
+}
+
 impl<T> ConvertToOwned for Vec<T>
 where
     T: ConvertToOwned,
@@ -403,8 +1001,22 @@ macro_rules! impl_conversion {
                 }
             }
         }
+
+        impl ConvertToString for $target<Str> {
+            type Owned = $target<String>;
+            fn convert_to_string(self) -> $target<String> {
+                $target {
+                    $(
+                        $target_field: self.$target_field.convert_to_string(),
+                    )*
+                    $(
+                        $other_field: self.$other_field,
+                    )*
+                }
+            }
+        }
     };
-    ($target:ident enum [$($tuple_variant:ident),*] [$($unit_variant:ident),*]) => {
+    ($target:ident enum [$($tuple_variant:ident),*] [$($unit_variant:ident),*] $({$struct_variant:ident [$($struct_field:ident),*]})?) => {
         impl ConvertToOwned for $target<Cow<'_, str>> {
             type Owned = $target;
             fn convert_to_owned(self, buf: &Str) -> $target {
@@ -415,6 +1027,30 @@ macro_rules! impl_conversion {
                     $(
                         Self::$unit_variant => $target::$unit_variant,
                     )*
+                    $(
+                        Self::$struct_variant { $($struct_field),* } => $target::$struct_variant {
+                            $($struct_field: $struct_field.convert_to_owned(buf),)*
+                        },
+                    )?
+                }
+            }
+        }
+
+        impl ConvertToString for $target<Str> {
+            type Owned = $target<String>;
+            fn convert_to_string(self) -> $target<String> {
+                match self {
+                    $(
+                        Self::$tuple_variant(val) => $target::$tuple_variant(val.convert_to_string()),
+                    )*
+                    $(
+                        Self::$unit_variant => $target::$unit_variant,
+                    )*
+                    $(
+                        Self::$struct_variant { $($struct_field),* } => $target::$struct_variant {
+                            $($struct_field: $struct_field.convert_to_string(),)*
+                        },
+                    )?
                 }
             }
         }
@@ -426,9 +1062,18 @@ macro_rules! impl_conversion {
                 self
             }
         }
+
+        impl ConvertToString for $target {
+            type Owned = $target;
+            fn convert_to_string(self) -> $target {
+                self
+            }
+        }
     }
 }
 
+pub(crate) use impl_conversion;
+
 // Enums for fields with multiple discrete values
 impl_conversion!(ResponseStatus);
 impl_conversion!(ItemStatus);
@@ -451,30 +1096,49 @@ impl_conversion!(StreamEvent enum [
     ResponseReasoningSummaryTextDelta,
     ResponseReasoningSummaryTextDone,
     ResponseReasoningSummaryPartDone,
-    ResponseCompleted
-] []);
+    ResponseWebSearchCallInProgress,
+    ResponseWebSearchCallSearching,
+    ResponseWebSearchCallCompleted,
+    ResponseCompleted,
+    ResponseFailed,
+    ResponseIncomplete,
+    Error
+] [] {Unknown [event_type, raw]});
 
 // Response lifecycle events
-impl_conversion!(ResponseCreatedData);
-impl_conversion!(ResponseInProgressData);
-impl_conversion!(ResponseCompletedData);
-impl_conversion!(ResponseMetadata);
+impl_conversion!(ResponseCreatedData struct [response] [sequence_number]);
+impl_conversion!(ResponseInProgressData struct [response] [sequence_number]);
+impl_conversion!(ResponseCompletedData struct [response] [sequence_number]);
+impl_conversion!(ResponseFailedData struct [response] [sequence_number]);
+impl_conversion!(ResponseIncompleteData struct [response] [sequence_number]);
+impl_conversion!(ErrorData struct [code, message, param] [sequence_number]);
+impl_conversion!(ResponseError struct [code, message] []);
+impl_conversion!(IncompleteDetails struct [reason] []);
 impl_conversion!(UsageInfo);
 
 // Output item events
 impl_conversion!(OutputItemAddedData struct [item] [output_index, sequence_number]);
 impl_conversion!(OutputItemDoneData struct [item] [output_index, sequence_number]);
 
-impl_conversion!(OutputItem enum [Message, Reasoning, FunctionCall] []);
+impl_conversion!(OutputItem enum [Message, Reasoning, FunctionCall, WebSearchCall] [] {Unknown [object_type, raw]});
 impl_conversion!(MessageItem struct [id, content] [status]);
-impl_conversion!(ReasoningItem struct [id, summary, encrypted_content] []);
-impl_conversion!(FunctionCallItem struct [call_id, name, arguments] [status]);
+impl_conversion!(ReasoningItem struct [id, summary, content, encrypted_content] []);
+impl_conversion!(FunctionCallItem struct [id, call_id, name, arguments] [status]);
+impl_conversion!(WebSearchCallItem struct [id, action] [status]);
+
+// Web search call events
+impl_conversion!(WebSearchCallEventData struct [item_id] [output_index, sequence_number]);
+impl_conversion!(WebSearchAction enum [Search, OpenPage, Find] [] {Unknown [object_type, raw]});
+impl_conversion!(SearchAction struct [query, sources] []);
+impl_conversion!(OpenPageAction struct [url] []);
+impl_conversion!(FindAction struct [url, pattern] []);
+impl_conversion!(WebSearchSource enum [] [] {Url [url]});
 
 // Content part events
 impl_conversion!(ContentPartAddedData struct [item_id, part] [output_index, content_index, sequence_number]);
 impl_conversion!(ContentPartDoneData struct [item_id, part] [output_index, content_index, sequence_number]);
 
-impl_conversion!(ContentPart enum [OutputText, ReasoningText, SummaryText] []);
+impl_conversion!(ContentPart enum [OutputText, ReasoningText, SummaryText] [] {Unknown [object_type, raw]});
 impl_conversion!(OutputTextPart struct [text, annotations] []);
 impl_conversion!(ReasoningTextPart struct [text] []);
 impl_conversion!(SummaryPart struct [content] []);
@@ -497,5 +1161,5 @@ impl_conversion!(FunctionCallArgumentsDoneData struct [item_id, name, arguments]
 
 // Annotation events
 impl_conversion!(AnnotationAddedData struct [item_id, annotation] [output_index, content_index, sequence_number, annotation_index]);
-impl_conversion!(Annotation enum [UrlCitation] []);
+impl_conversion!(Annotation enum [UrlCitation] [] {Unknown [object_type, raw]});
 impl_conversion!(UrlCitation struct [url, title] [start_index, end_index]);
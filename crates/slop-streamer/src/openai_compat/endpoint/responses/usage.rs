@@ -0,0 +1,186 @@
+//! Summing [`UsageInfo`] across many responses, for reporting what a batch of work used and cost
+
+use std::{collections::BTreeMap, ops::Deref};
+
+use serde::{Deserialize, Serialize};
+
+use crate::openai_compat::endpoint::responses::{
+    response::Response, stream::stream_item::UsageInfo,
+};
+
+/// Usage totals overall, per model and per caller-supplied tag such as a job or customer id.
+/// Serializes to plain JSON for exporting, and deserializes back so ledgers can be persisted and [merged](Self::merge).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageLedger {
+    pub total: UsageTotals,
+    #[serde(default)]
+    pub by_model: BTreeMap<String, UsageTotals>,
+    #[serde(default)]
+    pub by_tag: BTreeMap<String, UsageTotals>,
+}
+
+impl UsageLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add one response's usage to the total, and to `model` and `tag` where given
+    pub fn record(&mut self, model: Option<&str>, tag: Option<&str>, usage: &UsageInfo) {
+        self.total.add(usage);
+        if let Some(model) = model {
+            self.by_model
+                .entry(model.to_string())
+                .or_default()
+                .add(usage);
+        }
+        if let Some(tag) = tag {
+            self.by_tag.entry(tag.to_string()).or_default().add(usage);
+        }
+    }
+
+    /// [`record`](Self::record) a response under the model it reports, returns `false` if it carries no usage
+    pub fn record_response<T>(&mut self, response: &Response<T>, tag: Option<&str>) -> bool
+    where
+        T: Deref<Target = str>,
+    {
+        let Some(usage) = &response.usage else {
+            return false;
+        };
+        self.record(response.model.as_deref(), tag, usage);
+        true
+    }
+
+    /// Add another ledger's totals to this one
+    pub fn merge(&mut self, other: &UsageLedger) {
+        self.total.merge(&other.total);
+        for (model, totals) in &other.by_model {
+            self.by_model
+                .entry(model.clone())
+                .or_default()
+                .merge(totals);
+        }
+        for (tag, totals) in &other.by_tag {
+            self.by_tag.entry(tag.clone()).or_default().merge(totals);
+        }
+    }
+
+    pub fn model(&self, model: &str) -> Option<&UsageTotals> {
+        self.by_model.get(model)
+    }
+
+    pub fn tag(&self, tag: &str) -> Option<&UsageTotals> {
+        self.by_tag.get(tag)
+    }
+}
+
+/// Summed [`UsageInfo`]. Costs the provider didn't report count as 0.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageTotals {
+    /// Number of responses recorded
+    pub responses: u64,
+    /// How many of those used the caller's own provider key
+    pub byok_responses: u64,
+    pub input_tokens: u64,
+    pub cached_tokens: u64,
+    pub output_tokens: u64,
+    pub reasoning_tokens: u64,
+    pub total_tokens: u64,
+    /// In credits
+    pub cost: f64,
+    /// In USD
+    pub upstream_inference_cost: f64,
+    pub upstream_inference_input_cost: f64,
+    pub upstream_inference_output_cost: f64,
+}
+
+impl UsageTotals {
+    pub fn add(&mut self, usage: &UsageInfo) {
+        let cost_details = usage.cost_details.unwrap_or_default();
+
+        self.responses += 1;
+        self.byok_responses += u64::from(usage.is_byok.unwrap_or(false));
+        self.input_tokens += usage.input_tokens;
+        self.cached_tokens += usage.cached_tokens();
+        self.output_tokens += usage.output_tokens;
+        self.reasoning_tokens += usage.reasoning_tokens();
+        self.total_tokens += usage.total_tokens;
+        self.cost += usage.cost.unwrap_or(0.0);
+        self.upstream_inference_cost += cost_details.upstream_inference_cost.unwrap_or(0.0);
+        self.upstream_inference_input_cost +=
+            cost_details.upstream_inference_input_cost.unwrap_or(0.0);
+        self.upstream_inference_output_cost +=
+            cost_details.upstream_inference_output_cost.unwrap_or(0.0);
+    }
+
+    pub fn merge(&mut self, other: &UsageTotals) {
+        self.responses += other.responses;
+        self.byok_responses += other.byok_responses;
+        self.input_tokens += other.input_tokens;
+        self.cached_tokens += other.cached_tokens;
+        self.output_tokens += other.output_tokens;
+        self.reasoning_tokens += other.reasoning_tokens;
+        self.total_tokens += other.total_tokens;
+        self.cost += other.cost;
+        self.upstream_inference_cost += other.upstream_inference_cost;
+        self.upstream_inference_input_cost += other.upstream_inference_input_cost;
+        self.upstream_inference_output_cost += other.upstream_inference_output_cost;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        openai_compat::endpoint::responses::stream::stream_item::CostDetails,
+        samples::completed_response,
+    };
+
+    #[test]
+    fn test_sample_cost_details() {
+        let response = completed_response("google_gemini-3-pro-preview", "sample1_representative");
+        let usage = response.usage.unwrap();
+        assert_eq!(usage.is_byok, Some(false));
+        assert_eq!(
+            usage.cost_details,
+            Some(CostDetails {
+                upstream_inference_cost: Some(0.003364),
+                upstream_inference_input_cost: Some(0.00064),
+                upstream_inference_output_cost: Some(0.002724),
+            })
+        );
+    }
+
+    #[test]
+    fn test_ledger_groups_by_model_and_tag() {
+        let mut ledger = UsageLedger::new();
+        for (sample, tag) in [
+            ("sample1_representative", "nightly"),
+            ("sample2_web_search", "nightly"),
+            ("sample3_rejected", "adhoc"),
+        ] {
+            let response = completed_response("google_gemini-3-pro-preview", sample);
+            assert!(ledger.record_response(&response, Some(tag)));
+        }
+
+        let gemini = ledger.model("google/gemini-3-pro-preview").unwrap();
+        assert_eq!(gemini, &ledger.total);
+        assert_eq!(gemini.responses, 3);
+        assert_eq!(gemini.byok_responses, 0);
+        assert_eq!(gemini.input_tokens, 320 + 4356 + 13);
+        assert_eq!(gemini.reasoning_tokens, 104 + 708 + 721);
+
+        let nightly = ledger.tag("nightly").unwrap();
+        assert_eq!(nightly.responses, 2);
+        assert!((nightly.cost - (0.003364 + 0.04814)).abs() < 1e-9);
+        assert_eq!(ledger.tag("adhoc").unwrap().output_tokens, 996);
+
+        // Round trips through the export format, and merging doubles everything
+        let json = serde_json::to_string(&ledger).unwrap();
+        let mut restored: UsageLedger = serde_json::from_str(&json).unwrap();
+        restored.merge(&ledger);
+        assert_eq!(restored.total.responses, 6);
+        assert_eq!(restored.tag("adhoc").unwrap().total_tokens, 2 * 1009);
+        assert_eq!(restored.by_model.len(), 1);
+    }
+}
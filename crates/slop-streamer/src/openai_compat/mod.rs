@@ -1,2 +1,3 @@
-
+pub mod api_error;
 pub mod endpoint;
+pub(crate) mod serde_helpers;
@@ -0,0 +1,12 @@
+//! `deserialize_with` functions shared by the response types of several endpoints
+
+use serde::{Deserialize, Deserializer};
+
+/// Some servers send `null` where they mean an empty list
+pub(crate) fn null_as_default<'de, D, V>(deserializer: D) -> Result<V, D::Error>
+where
+    D: Deserializer<'de>,
+    V: Deserialize<'de> + Default,
+{
+    Ok(Option::<V>::deserialize(deserializer)?.unwrap_or_default())
+}
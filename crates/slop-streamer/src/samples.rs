@@ -0,0 +1,56 @@
+//! The recorded provider streams in `samples/{model}/{sample}.txt`, shared by the tests
+
+use std::{borrow::Cow, path::PathBuf};
+
+use bytes_utils::Str;
+
+use crate::openai_compat::endpoint::responses::{
+    response::Response,
+    stream::stream_item::{ConvertToOwned, StreamEvent},
+};
+
+pub(crate) const DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/samples");
+
+/// `sample` is the file name without the `.txt`
+pub(crate) fn path(model: &str, sample: &str) -> PathBuf {
+    PathBuf::from(DIR)
+        .join(model)
+        .join(format!("{}.txt", sample))
+}
+
+pub(crate) fn read(model: &str, sample: &str) -> Str {
+    let path = path(model, sample);
+    std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e))
+        .into()
+}
+
+/// The sample's events, with the whole file arriving as one chunk
+#[cfg(not(feature = "miri"))] // Only the tests that don't work with miri stream samples
+pub(crate) fn stream(
+    model: &str,
+    sample: &str,
+) -> crate::openai_compat::endpoint::responses::stream::OAICompatResponsesStream<
+    impl futures::Stream<Item = Result<bytes::Bytes, std::convert::Infallible>>,
+> {
+    let content = read(model, sample).into_inner();
+    crate::openai_compat::endpoint::responses::stream::OAICompatResponsesStream::new(
+        futures::stream::iter([Ok(content)]),
+    )
+}
+
+/// The full response embedded in the sample's `response.completed` event
+pub(crate) fn completed_response(model: &str, sample: &str) -> Response {
+    let content = read(model, sample);
+    let completed = content
+        .lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .find(|data| data.contains(r#""type":"response.completed""#))
+        .unwrap_or_else(|| panic!("{}/{} has no response.completed", model, sample));
+
+    let event: StreamEvent<Cow<'_, str>> = serde_json::from_str(completed).unwrap();
+    let StreamEvent::ResponseCompleted(data) = event else {
+        panic!("{}/{} didn't parse as response.completed", model, sample);
+    };
+    data.response.convert_to_owned(&content)
+}
@@ -1,5 +0,0 @@
-use schemars::JsonSchema;
-
-pub trait Tool: Send + Sync {
-    type Input: JsonSchema;
-}
@@ -0,0 +1,87 @@
+use schemars::{JsonSchema, Schema};
+
+pub mod registry;
+
+pub use registry::{CallableTool, ToolRegistry};
+
+pub trait Tool: Send + Sync {
+    type Input: JsonSchema;
+
+    fn name(&self) -> &str;
+
+    fn description(&self) -> &str;
+
+    /// Whether the provider should enforce the parameter schema exactly, `None` leaves it up to the provider
+    fn strict(&self) -> Option<bool> {
+        None
+    }
+}
+
+/// Object safe view of a [`Tool`] with the input type already turned into a schema, so tools with different inputs can live in the same collection
+///
+/// Implemented for every [`Tool`], you shouldn't need to implement this yourself
+pub trait ToolDefinition: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn description(&self) -> &str;
+
+    fn strict(&self) -> Option<bool>;
+
+    fn parameters(&self) -> Schema;
+}
+
+impl<T> ToolDefinition for T
+where
+    T: Tool + ?Sized,
+{
+    fn name(&self) -> &str {
+        <T as Tool>::name(self)
+    }
+
+    fn description(&self) -> &str {
+        <T as Tool>::description(self)
+    }
+
+    fn strict(&self) -> Option<bool> {
+        <T as Tool>::strict(self)
+    }
+
+    fn parameters(&self) -> Schema {
+        let mut schema = schemars::schema_for!(T::Input);
+        // Providers don't want to be told which draft the parameters are, and some reject the key outright
+        schema.remove("$schema");
+        schema
+    }
+}
+
+/// Anything that can be viewed as a [`ToolDefinition`], lets collections hold concrete tools or boxed trait objects alike
+pub trait AsToolDefinition {
+    fn as_tool_definition(&self) -> &dyn ToolDefinition;
+}
+
+impl<T> AsToolDefinition for T
+where
+    T: Tool,
+{
+    fn as_tool_definition(&self) -> &dyn ToolDefinition {
+        self
+    }
+}
+
+impl AsToolDefinition for &dyn ToolDefinition {
+    fn as_tool_definition(&self) -> &dyn ToolDefinition {
+        *self
+    }
+}
+
+impl AsToolDefinition for Box<dyn ToolDefinition> {
+    fn as_tool_definition(&self) -> &dyn ToolDefinition {
+        self.as_ref()
+    }
+}
+
+impl AsToolDefinition for std::sync::Arc<dyn ToolDefinition> {
+    fn as_tool_definition(&self) -> &dyn ToolDefinition {
+        self.as_ref()
+    }
+}
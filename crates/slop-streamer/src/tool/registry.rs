@@ -0,0 +1,416 @@
+//! Routing of function calls the model made back to the [`CallableTool`] they were meant for
+
+use std::{borrow::Cow, future::Future};
+
+use futures::future::BoxFuture;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    openai_compat::endpoint::{
+        chat_completions::request::message::{self, ToolMessage},
+        responses::{
+            request::{
+                input_type::{AsInputItem, InputFunctionCallOutput, InputItem},
+                tools::ToolCollection,
+            },
+            stream::stream_item::FunctionCallItem,
+        },
+    },
+    tool::{Tool, ToolDefinition},
+};
+
+/// A [`Tool`] this process can actually run
+pub trait CallableTool: Tool<Input: DeserializeOwned + Send> {
+    /// Serialized to JSON before being handed back to the model
+    type Output: Serialize;
+
+    fn call(&self, input: Self::Input) -> impl Future<Output = Self::Output> + Send;
+}
+
+/// Object safe form of [`CallableTool`] so the registry can hold tools with different inputs and outputs
+trait ErasedCallableTool: Send + Sync {
+    fn definition(&self) -> &dyn ToolDefinition;
+
+    fn call_erased<'a>(
+        &'a self,
+        arguments: &str,
+    ) -> Result<BoxFuture<'a, Result<String, ToolCallErrorKind>>, ToolCallErrorKind>;
+}
+
+impl<T> ErasedCallableTool for T
+where
+    T: CallableTool,
+{
+    fn definition(&self) -> &dyn ToolDefinition {
+        self
+    }
+
+    fn call_erased<'a>(
+        &'a self,
+        arguments: &str,
+    ) -> Result<BoxFuture<'a, Result<String, ToolCallErrorKind>>, ToolCallErrorKind> {
+        let input = serde_json::from_str::<T::Input>(arguments)
+            .map_err(ToolCallErrorKind::InvalidArguments)?;
+
+        Ok(Box::pin(async move {
+            let output = self.call(input).await;
+            serde_json::to_string(&output).map_err(ToolCallErrorKind::Output)
+        }))
+    }
+}
+
+/// A set of [`CallableTool`]s that can be sent with a request (it is a [`ToolCollection`]) and then run the calls that come back
+#[derive(Default)]
+pub struct ToolRegistry {
+    tools: Vec<Box<dyn ErasedCallableTool>>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a tool, replacing any existing tool with the same name
+    pub fn register<T>(&mut self, tool: T) -> &mut Self
+    where
+        T: CallableTool + 'static,
+    {
+        let tool: Box<dyn ErasedCallableTool> = Box::new(tool);
+        match self.position(tool.definition().name()) {
+            Some(idx) => self.tools[idx] = tool,
+            None => self.tools.push(tool),
+        }
+        self
+    }
+
+    /// Builder style [`ToolRegistry::register`]
+    pub fn with<T>(mut self, tool: T) -> Self
+    where
+        T: CallableTool + 'static,
+    {
+        self.register(tool);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn ToolDefinition> {
+        self.position(name).map(|idx| self.tools[idx].definition())
+    }
+
+    pub fn len(&self) -> usize {
+        self.tools.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn ToolDefinition> {
+        self.tools.iter().map(|tool| tool.definition())
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.tools
+            .iter()
+            .position(|tool| tool.definition().name() == name)
+    }
+
+    /// Run the tool the model asked for, the result carries the same `call_id` either way so it can go straight into the next request's input
+    pub async fn call<T>(
+        &self,
+        item: &FunctionCallItem<T>,
+    ) -> Result<ToolCallOutput<T>, ToolCallError<T>>
+    where
+        T: AsRef<str> + Clone,
+    {
+        let error = |kind| ToolCallError {
+            call_id: item.call_id.clone(),
+            name: item.name.as_ref().to_string(),
+            kind,
+        };
+
+        let Some(idx) = self.position(item.name.as_ref()) else {
+            return Err(error(ToolCallErrorKind::UnknownTool));
+        };
+
+        let output = self.tools[idx]
+            .call_erased(item.arguments.as_ref())
+            .map_err(error)?
+            .await
+            .map_err(error)?;
+
+        Ok(ToolCallOutput {
+            call_id: item.call_id.clone(),
+            output,
+        })
+    }
+
+    /// [`ToolRegistry::call`] but failures are turned into outputs for the model to read, which is usually what you want when running an agent loop
+    pub async fn call_or_report<T>(&self, item: &FunctionCallItem<T>) -> ToolCallOutput<T>
+    where
+        T: AsRef<str> + Clone,
+    {
+        match self.call(item).await {
+            Ok(output) => output,
+            Err(err) => err.into_output(),
+        }
+    }
+}
+
+impl std::fmt::Debug for ToolRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(|tool| tool.name()))
+            .finish()
+    }
+}
+
+impl ToolCollection for ToolRegistry {
+    fn tool_definitions(&self) -> impl Iterator<Item = &dyn ToolDefinition> {
+        self.iter()
+    }
+
+    fn is_empty(&self) -> bool {
+        ToolRegistry::is_empty(self)
+    }
+}
+
+/// The successful result of a tool call, usable as an [`InputItem`] or a [`ToolMessage`] in the next request
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ToolCallOutput<T> {
+    pub call_id: T,
+    /// JSON encoded [`CallableTool::Output`]
+    pub output: String,
+}
+
+impl<T> InputFunctionCallOutput for ToolCallOutput<T>
+where
+    T: AsRef<str>,
+{
+    fn call_id(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self.call_id.as_ref())
+    }
+
+    fn output(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.output)
+    }
+}
+
+impl<T> AsInputItem for ToolCallOutput<T>
+where
+    T: AsRef<str>,
+{
+    fn erase_variant(&self) -> &dyn InputItem {
+        InputFunctionCallOutput::as_wrapper_ref(self)
+    }
+}
+
+impl<T> ToolMessage for ToolCallOutput<T>
+where
+    T: AsRef<str>,
+{
+    fn tool_call_id(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self.call_id.as_ref())
+    }
+
+    fn content(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.output)
+    }
+}
+
+impl<T> message::AsChatMessage for ToolCallOutput<T>
+where
+    T: AsRef<str>,
+{
+    fn erase_variant(&self) -> &dyn message::ChatMessage {
+        ToolMessage::as_wrapper_ref(self)
+    }
+}
+
+#[derive(Debug)]
+pub enum ToolCallErrorKind {
+    /// The model asked for a tool that isn't in the registry
+    UnknownTool,
+    /// The arguments didn't deserialize into the tool's input
+    InvalidArguments(serde_json::Error),
+    /// The tool's output couldn't be serialized
+    Output(serde_json::Error),
+}
+
+/// A tool call that couldn't be run, also an [`InputFunctionCallOutput`] and a [`ToolMessage`] so the model can be told what went wrong
+#[derive(Debug)]
+pub struct ToolCallError<T> {
+    pub call_id: T,
+    pub name: String,
+    pub kind: ToolCallErrorKind,
+}
+
+impl<T> ToolCallError<T> {
+    /// The JSON body sent back to the model for this error
+    pub fn report(&self) -> String {
+        serde_json::json!({ "error": self.to_string() }).to_string()
+    }
+
+    pub fn into_output(self) -> ToolCallOutput<T> {
+        let output = self.report();
+        ToolCallOutput {
+            call_id: self.call_id,
+            output,
+        }
+    }
+}
+
+impl<T> std::fmt::Display for ToolCallError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ToolCallErrorKind::UnknownTool => write!(f, "unknown tool `{}`", self.name),
+            ToolCallErrorKind::InvalidArguments(error) => {
+                write!(f, "invalid arguments for tool `{}`: {}", self.name, error)
+            }
+            ToolCallErrorKind::Output(error) => {
+                write!(
+                    f,
+                    "failed to serialize output of tool `{}`: {}",
+                    self.name, error
+                )
+            }
+        }
+    }
+}
+
+impl<T> std::error::Error for ToolCallError<T> where T: std::fmt::Debug {}
+
+impl<T> InputFunctionCallOutput for ToolCallError<T>
+where
+    T: AsRef<str>,
+{
+    fn call_id(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self.call_id.as_ref())
+    }
+
+    fn output(&self) -> Cow<'_, str> {
+        Cow::Owned(self.report())
+    }
+}
+
+impl<T> AsInputItem for ToolCallError<T>
+where
+    T: AsRef<str>,
+{
+    fn erase_variant(&self) -> &dyn InputItem {
+        InputFunctionCallOutput::as_wrapper_ref(self)
+    }
+}
+
+impl<T> ToolMessage for ToolCallError<T>
+where
+    T: AsRef<str>,
+{
+    fn tool_call_id(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self.call_id.as_ref())
+    }
+
+    fn content(&self) -> Cow<'_, str> {
+        Cow::Owned(self.report())
+    }
+}
+
+impl<T> message::AsChatMessage for ToolCallError<T>
+where
+    T: AsRef<str>,
+{
+    fn erase_variant(&self) -> &dyn message::ChatMessage {
+        ToolMessage::as_wrapper_ref(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use schemars::JsonSchema;
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::openai_compat::endpoint::responses::stream::stream_item::ItemStatus;
+
+    #[derive(Deserialize, JsonSchema)]
+    struct ConvertInput {
+        value: f64,
+    }
+
+    struct CelsiusToFahrenheit;
+
+    impl Tool for CelsiusToFahrenheit {
+        type Input = ConvertInput;
+
+        fn name(&self) -> &str {
+            "convert_temperature"
+        }
+
+        fn description(&self) -> &str {
+            "Convert celsius to fahrenheit"
+        }
+    }
+
+    impl CallableTool for CelsiusToFahrenheit {
+        type Output = f64;
+
+        async fn call(&self, input: ConvertInput) -> f64 {
+            input.value * 9.0 / 5.0 + 32.0
+        }
+    }
+
+    fn function_call(name: &str, arguments: &str) -> FunctionCallItem<String> {
+        FunctionCallItem {
+            id: None,
+            call_id: "call_1".to_string(),
+            name: name.to_string(),
+            arguments: arguments.to_string(),
+            status: ItemStatus::Completed,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_call_routes_by_name() {
+        let registry = ToolRegistry::new().with(CelsiusToFahrenheit);
+
+        let output = registry
+            .call(&function_call("convert_temperature", r#"{"value":100}"#))
+            .await
+            .unwrap();
+
+        assert_eq!(output.call_id, "call_1");
+        assert_eq!(output.output, "212.0");
+
+        let json = serde_json::to_string(output.erase_variant()).unwrap();
+        assert_eq!(
+            json,
+            r#"{"call_id":"call_1","output":"212.0","type":"function_call_output"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_call_reports_errors() {
+        let registry = ToolRegistry::new().with(CelsiusToFahrenheit);
+
+        let err = registry
+            .call(&function_call("get_current_weather", "{}"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err.kind, ToolCallErrorKind::UnknownTool));
+        assert_eq!(err.call_id, "call_1");
+
+        let err = registry
+            .call(&function_call("convert_temperature", r#"{"value":"hot"}"#))
+            .await
+            .unwrap_err();
+        assert!(matches!(err.kind, ToolCallErrorKind::InvalidArguments(_)));
+
+        let output = err.into_output();
+        let report: serde_json::Value = serde_json::from_str(&output.output).unwrap();
+        assert!(
+            report["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("invalid arguments for tool `convert_temperature`")
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! [`HttpTransport`] over a hyper-util [`Client`]
+
+use bytes::Bytes;
+use futures::{TryStreamExt, stream::MapErr};
+use http_body_util::{BodyDataStream, Full};
+use hyper::body::Incoming;
+use hyper_util::{
+    client::legacy::{
+        Client,
+        connect::{Connect, HttpConnector},
+    },
+    rt::TokioExecutor,
+};
+
+use crate::transport::{
+    HttpRequest, HttpResponse, HttpTransport, TransportError, has_transient_io_source,
+};
+
+/// Bring your own connector for TLS (e.g. hyper-rustls), [`HyperTransport::new`] only speaks plain HTTP
+#[derive(Debug, Clone)]
+pub struct HyperTransport<C = HttpConnector> {
+    pub client: Client<C, Full<Bytes>>,
+}
+
+impl HyperTransport {
+    pub fn new() -> Self {
+        Self {
+            client: Client::builder(TokioExecutor::new()).build_http(),
+        }
+    }
+}
+
+impl Default for HyperTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> HyperTransport<C> {
+    pub fn with_client(client: Client<C, Full<Bytes>>) -> Self {
+        Self { client }
+    }
+}
+
+pub type HyperBody = MapErr<BodyDataStream<Incoming>, fn(hyper::Error) -> HyperTransportError>;
+
+impl<C> HttpTransport for HyperTransport<C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    type Error = HyperTransportError;
+    type Body = HyperBody;
+
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse<Self::Body>, Self::Error> {
+        let mut builder = http::Request::builder()
+            .method(request.method)
+            .uri(request.url.as_str());
+        if let Some(headers) = builder.headers_mut() {
+            *headers = request.headers;
+        }
+        let request = builder
+            .body(Full::new(request.body))
+            .map_err(HyperTransportError::Request)?;
+
+        let (parts, body) = self
+            .client
+            .request(request)
+            .await
+            .map_err(HyperTransportError::Client)?
+            .into_parts();
+
+        Ok(HttpResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body: BodyDataStream::new(body).map_err(HyperTransportError::Body as fn(_) -> _),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum HyperTransportError {
+    /// The URL or headers couldn't be turned into an `http::Request`
+    Request(http::Error),
+    Client(hyper_util::client::legacy::Error),
+    /// The connection failed while the body was being read
+    Body(hyper::Error),
+}
+
+impl std::fmt::Display for HyperTransportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HyperTransportError::Request(error) => write!(f, "invalid request: {}", error),
+            HyperTransportError::Client(error) => error.fmt(f),
+            HyperTransportError::Body(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for HyperTransportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HyperTransportError::Request(error) => Some(error),
+            HyperTransportError::Client(error) => Some(error),
+            HyperTransportError::Body(error) => Some(error),
+        }
+    }
+}
+
+impl TransportError for HyperTransportError {
+    fn is_transient(&self) -> bool {
+        match self {
+            HyperTransportError::Request(_) => false,
+            HyperTransportError::Client(error) => {
+                error.is_connect()
+                    || std::error::Error::source(error)
+                        .and_then(|source| source.downcast_ref::<hyper::Error>())
+                        .is_some_and(is_transient_hyper_error)
+                    || has_transient_io_source(error)
+            }
+            HyperTransportError::Body(error) => is_transient_hyper_error(error),
+        }
+    }
+}
+
+/// Timed out, or the connection closed partway through the message
+fn is_transient_hyper_error(error: &hyper::Error) -> bool {
+    error.is_timeout() || error.is_incomplete_message() || has_transient_io_source(error)
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "miri"))] // These tests don't work with miri
+mod tests {
+    use super::*;
+    use crate::transport::test_server::round_trip;
+
+    #[tokio::test]
+    async fn test_round_trip() {
+        round_trip(HyperTransport::new()).await;
+    }
+}
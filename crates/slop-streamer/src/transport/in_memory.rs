@@ -0,0 +1,162 @@
+//! An [`HttpTransport`] that answers from a queue of canned responses, for testing client code without a network
+
+use std::{collections::VecDeque, path::Path, sync::Mutex};
+
+use bytes::Bytes;
+use futures::stream;
+use http::{HeaderMap, HeaderValue, StatusCode, header::CONTENT_TYPE};
+
+use crate::transport::{HttpRequest, HttpResponse, HttpTransport, TransportError};
+
+#[derive(Debug, Clone)]
+pub struct CannedResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+    /// Split the body into chunks of at most this many bytes, to exercise reassembly of events split across reads
+    pub chunk_size: Option<usize>,
+    /// Cut the body off after this many bytes with [`InMemoryTransportError::ConnectionReset`]
+    pub fail_after: Option<usize>,
+}
+
+impl CannedResponse {
+    pub fn new(status: StatusCode, content_type: &'static str, body: impl Into<Bytes>) -> Self {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+        Self {
+            status,
+            headers,
+            body: body.into(),
+            chunk_size: None,
+            fail_after: None,
+        }
+    }
+
+    /// A 200 `text/event-stream` response replaying a recorded stream laid out as `{samples_dir}/{model}/{sample}.txt`,
+    /// e.g. `sample("samples", "openai_gpt-5.2", "sample1_representative")` for the ones in this repository
+    pub fn sample(
+        samples_dir: impl AsRef<Path>,
+        model: &str,
+        sample: &str,
+    ) -> std::io::Result<Self> {
+        let path = samples_dir
+            .as_ref()
+            .join(model)
+            .join(sample)
+            .with_extension("txt");
+        let body = std::fs::read(path)?;
+        Ok(Self::new(StatusCode::OK, "text/event-stream", body))
+    }
+
+    pub fn with_header(mut self, name: &'static str, value: &'static str) -> Self {
+        self.headers.insert(name, HeaderValue::from_static(value));
+        self
+    }
+
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = Some(chunk_size.max(1));
+        self
+    }
+
+    pub fn with_failure_after(mut self, bytes: usize) -> Self {
+        self.fail_after = Some(bytes);
+        self
+    }
+}
+
+/// Responses are handed out in the order they were pushed, and every request sent is kept for inspection
+#[derive(Debug, Default)]
+pub struct InMemoryTransport {
+    responses: Mutex<VecDeque<CannedResponse>>,
+    requests: Mutex<Vec<HttpRequest>>,
+}
+
+impl InMemoryTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&self, response: CannedResponse) {
+        self.responses.lock().unwrap().push_back(response);
+    }
+
+    pub fn with_response(self, response: CannedResponse) -> Self {
+        self.push(response);
+        self
+    }
+
+    /// Every request sent so far, oldest first
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+pub type InMemoryBody = stream::Iter<std::vec::IntoIter<Result<Bytes, InMemoryTransportError>>>;
+
+impl HttpTransport for InMemoryTransport {
+    type Error = InMemoryTransportError;
+    type Body = InMemoryBody;
+
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse<Self::Body>, Self::Error> {
+        let response = self.responses.lock().unwrap().pop_front();
+        let Some(response) = response else {
+            return Err(InMemoryTransportError::NoResponse {
+                method: request.method.to_string(),
+                url: request.url.to_string(),
+            });
+        };
+        self.requests.lock().unwrap().push(request);
+
+        let body = match response.fail_after {
+            Some(fail_after) => response.body.slice(..fail_after.min(response.body.len())),
+            None => response.body,
+        };
+        let chunk_size = response.chunk_size.unwrap_or(body.len()).max(1);
+        let mut chunks = (0..body.len())
+            .step_by(chunk_size)
+            .map(|start| {
+                let end = (start + chunk_size).min(body.len());
+                Ok(body.slice(start..end))
+            })
+            .collect::<Vec<_>>();
+        if response.fail_after.is_some() {
+            chunks.push(Err(InMemoryTransportError::ConnectionReset));
+        }
+
+        Ok(HttpResponse {
+            status: response.status,
+            headers: response.headers,
+            body: stream::iter(chunks),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InMemoryTransportError {
+    /// More requests were sent than responses were pushed
+    NoResponse { method: String, url: String },
+    /// Injected with [`CannedResponse::with_failure_after`]
+    ConnectionReset,
+}
+
+impl std::fmt::Display for InMemoryTransportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InMemoryTransportError::NoResponse { method, url } => {
+                write!(f, "no canned response left for {} {}", method, url)
+            }
+            InMemoryTransportError::ConnectionReset => "connection reset".fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for InMemoryTransportError {}
+
+impl TransportError for InMemoryTransportError {
+    fn is_transient(&self) -> bool {
+        match self {
+            InMemoryTransportError::NoResponse { .. } => false,
+            InMemoryTransportError::ConnectionReset => true,
+        }
+    }
+}
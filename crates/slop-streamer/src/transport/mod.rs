@@ -0,0 +1,140 @@
+//! The request side of talking to a provider, kept behind a trait so the same client code runs on reqwest, hyper or canned responses in tests
+
+use std::pin::pin;
+
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt};
+use http::{HeaderMap, Method, StatusCode};
+use url::Url;
+
+#[cfg(feature = "hyper")]
+pub mod hyper;
+pub mod in_memory;
+#[cfg(feature = "reqwest")]
+pub mod reqwest;
+pub mod retry;
+#[cfg(all(
+    test,
+    not(feature = "miri"),
+    any(feature = "reqwest", feature = "hyper")
+))]
+mod test_server;
+
+pub use in_memory::{CannedResponse, InMemoryTransport};
+
+/// Sends a single HTTP request, the response body is left as a stream so it can be fed straight into
+/// [`OAICompatResponsesStream`](crate::openai_compat::endpoint::responses::stream::OAICompatResponsesStream)
+pub trait HttpTransport {
+    type Error: TransportError;
+    type Body: Stream<Item = Result<Bytes, Self::Error>> + Send + 'static;
+
+    /// Resolves once the status and headers are in, non-2xx statuses are not errors at this level
+    fn send(
+        &self,
+        request: HttpRequest,
+    ) -> impl Future<Output = Result<HttpResponse<Self::Body>, Self::Error>> + Send;
+}
+
+/// Lets the retry logic tell a dropped connection from a request that will fail the same way every time
+pub trait TransportError: std::error::Error + Send + Sync + 'static {
+    /// Whether the connection failed to open, timed out or was reset, so sending the same request again might succeed
+    fn is_transient(&self) -> bool;
+}
+
+/// Whether anything in `error`'s source chain is an IO error from the connection dropping or timing out
+#[cfg(any(feature = "reqwest", feature = "hyper"))]
+fn has_transient_io_source(error: &(dyn std::error::Error + 'static)) -> bool {
+    use std::io::ErrorKind;
+
+    let mut source = Some(error);
+    while let Some(error) = source {
+        if let Some(error) = error.downcast_ref::<std::io::Error>()
+            && matches!(
+                error.kind(),
+                ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::BrokenPipe
+                    | ErrorKind::TimedOut
+                    | ErrorKind::UnexpectedEof
+            )
+        {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
+impl<H> HttpTransport for &H
+where
+    H: HttpTransport + Sync,
+{
+    type Error = H::Error;
+    type Body = H::Body;
+
+    fn send(
+        &self,
+        request: HttpRequest,
+    ) -> impl Future<Output = Result<HttpResponse<Self::Body>, Self::Error>> + Send {
+        (**self).send(request)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl HttpRequest {
+    pub fn get(url: Url) -> Self {
+        Self {
+            method: Method::GET,
+            url,
+            headers: HeaderMap::new(),
+            body: Bytes::new(),
+        }
+    }
+
+    pub fn post(url: Url, body: impl Into<Bytes>) -> Self {
+        Self {
+            method: Method::POST,
+            url,
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct HttpResponse<B> {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: B,
+}
+
+impl<B, E> HttpResponse<B>
+where
+    B: Stream<Item = Result<Bytes, E>>,
+{
+    /// Read the whole body, for non-streaming responses and error bodies
+    pub async fn collect(self) -> Result<Bytes, E> {
+        let mut body = pin!(self.body);
+        let Some(first) = body.next().await.transpose()? else {
+            return Ok(Bytes::new());
+        };
+
+        // Most bodies arrive in one chunk, only copy when they don't
+        let Some(second) = body.next().await.transpose()? else {
+            return Ok(first);
+        };
+        let mut buf = BytesMut::from(first);
+        buf.extend_from_slice(&second);
+        while let Some(chunk) = body.next().await.transpose()? {
+            buf.extend_from_slice(&chunk);
+        }
+        Ok(buf.freeze())
+    }
+}
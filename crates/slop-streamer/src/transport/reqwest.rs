@@ -0,0 +1,59 @@
+//! [`HttpTransport`] over a [`reqwest::Client`]
+
+use bytes::Bytes;
+use futures::{StreamExt, stream::BoxStream};
+
+use crate::transport::{
+    HttpRequest, HttpResponse, HttpTransport, TransportError, has_transient_io_source,
+};
+
+/// Clone the client you already have configured (timeouts, proxies, ...) into this, reqwest clients are cheap to clone
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    pub client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl HttpTransport for ReqwestTransport {
+    type Error = reqwest::Error;
+    type Body = BoxStream<'static, Result<Bytes, reqwest::Error>>;
+
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse<Self::Body>, Self::Error> {
+        let response = self
+            .client
+            .request(request.method, request.url)
+            .headers(request.headers)
+            .body(request.body)
+            .send()
+            .await?;
+
+        Ok(HttpResponse {
+            status: response.status(),
+            headers: response.headers().clone(),
+            body: response.bytes_stream().boxed(),
+        })
+    }
+}
+
+impl TransportError for reqwest::Error {
+    fn is_transient(&self) -> bool {
+        self.is_connect() || self.is_timeout() || has_transient_io_source(self)
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "miri"))] // These tests don't work with miri
+mod tests {
+    use super::*;
+    use crate::transport::test_server::round_trip;
+
+    #[tokio::test]
+    async fn test_round_trip() {
+        round_trip(ReqwestTransport::default()).await;
+    }
+}
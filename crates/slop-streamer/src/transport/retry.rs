@@ -0,0 +1,365 @@
+//! When and how long to wait before sending a request again after a transient failure
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::BuildHasher,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use http::{HeaderMap, StatusCode};
+
+/// Lives with the stream error so [`OAICompatResponsesStreamError::RetriesExhausted`](crate::openai_compat::endpoint::responses::stream::OAICompatResponsesStreamError::RetriesExhausted) exists without the `client` feature
+pub use crate::openai_compat::endpoint::responses::stream::FailedAttempt;
+
+/// Exponential backoff, `initial_delay * 2^n` capped at `max_delay`, with a server's `Retry-After` used as a floor
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Including the first one, so `1` never retries
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    /// Give up rather than wait past this much time since the first attempt
+    pub deadline: Option<Duration>,
+    /// Pick each delay at random between half and all of the backoff, so clients that failed together don't retry together
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            deadline: None,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn with_initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// The backoff after the `failures`th failed attempt (starting at 1), before any `Retry-After`
+    pub fn backoff(&self, failures: u32) -> Duration {
+        let exponent = failures.saturating_sub(1).min(31);
+        let backoff = self
+            .initial_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+
+        if self.jitter {
+            backoff.mul_f64(0.5 + random_fraction() / 2.0)
+        } else {
+            backoff
+        }
+    }
+
+    /// How long to wait before the next attempt, `None` if there shouldn't be one
+    pub fn next_delay(
+        &self,
+        failures: u32,
+        retry_after: Option<Duration>,
+        elapsed: Duration,
+    ) -> Option<Duration> {
+        if failures >= self.max_attempts {
+            return None;
+        }
+
+        let delay = self.backoff(failures).max(retry_after.unwrap_or_default());
+        match self.deadline {
+            Some(deadline) if elapsed + delay > deadline => None,
+            _ => Some(delay),
+        }
+    }
+}
+
+/// Statuses that mean the request wasn't processed and sending it again may succeed
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::REQUEST_TIMEOUT
+            | StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// How long the server asked us to wait, from `Retry-After` (or OpenAI's `retry-after-ms`),
+/// and for 429s also the rate limit reset headers OpenAI (`x-ratelimit-reset-requests`, `x-ratelimit-reset-tokens`)
+/// and OpenRouter (`x-ratelimit-reset`) send
+pub fn retry_after(status: StatusCode, headers: &HeaderMap, now: SystemTime) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+
+    if let Some(delay) = header("retry-after-ms")
+        .and_then(|ms| ms.trim().parse::<f64>().ok())
+        .and_then(|ms| Duration::try_from_secs_f64(ms / 1000.0).ok())
+    {
+        return Some(delay);
+    }
+
+    if let Some(value) = header("retry-after") {
+        let value = value.trim();
+        if let Ok(secs) = value.parse::<u64>() {
+            return Some(Duration::from_secs(secs));
+        }
+        if let Some(at) = parse_http_date(value) {
+            return Some(at.duration_since(now).unwrap_or_default());
+        }
+    }
+
+    if status != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+
+    // Both limits have to have reset before the request can go through
+    let openai = ["x-ratelimit-reset-requests", "x-ratelimit-reset-tokens"]
+        .into_iter()
+        .filter_map(|name| header(name).and_then(parse_go_duration))
+        .max();
+    if openai.is_some() {
+        return openai;
+    }
+
+    // A unix timestamp in milliseconds from OpenRouter, but be lenient about seconds and relative values
+    let reset = header("x-ratelimit-reset")?.trim().parse::<u64>().ok()?;
+    let at = match reset {
+        ms if ms > 100_000_000_000 => UNIX_EPOCH + Duration::from_millis(ms),
+        secs if secs > 1_000_000_000 => UNIX_EPOCH + Duration::from_secs(secs),
+        secs => return Some(Duration::from_secs(secs)),
+    };
+    Some(at.duration_since(now).unwrap_or_default())
+}
+
+/// Durations like `1s`, `6m0s` or `20ms`
+fn parse_go_duration(value: &str) -> Option<Duration> {
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return None;
+    }
+
+    let mut total = Duration::ZERO;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let number = rest[..number_len].parse::<f64>().ok()?;
+        rest = &rest[number_len..];
+
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let secs_per_unit = match &rest[..unit_len] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            "us" | "µs" => 0.000_001,
+            "ns" => 0.000_000_001,
+            _ => return None,
+        };
+        rest = &rest[unit_len..];
+
+        total += Duration::try_from_secs_f64(number * secs_per_unit).ok()?;
+    }
+    Some(total)
+}
+
+/// The IMF-fixdate form, `Sun, 06 Nov 1994 08:49:37 GMT`, which is the only one servers are allowed to send
+fn parse_http_date(value: &str) -> Option<SystemTime> {
+    let parts: [&str; 6] = value
+        .split_ascii_whitespace()
+        .collect::<Vec<_>>()
+        .try_into()
+        .ok()?;
+    let [_weekday, day, month, year, time, "GMT"] = parts else {
+        return None;
+    };
+
+    let day = day.parse::<u64>().ok()?;
+    let month = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ]
+    .iter()
+    .position(|name| *name == month)? as u64
+        + 1;
+    let year = year.parse::<u64>().ok()?;
+    let [hours, minutes, seconds] = time
+        .split(':')
+        .map(|part| part.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?
+        .try_into()
+        .ok()?;
+
+    // The format only has room for four digit years, which also keeps the arithmetic below from overflowing
+    if !(1..=31).contains(&day)
+        || !(1970..=9999).contains(&year)
+        || hours > 23
+        || minutes > 59
+        || seconds > 60
+    {
+        return None;
+    }
+
+    // Days since the epoch for a proleptic Gregorian date, shifted so the year starts in March and leap days come last
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = y / 400;
+    let year_of_era = y % 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = (era * 146_097 + day_of_era).checked_sub(719_468)?;
+
+    UNIX_EPOCH.checked_add(Duration::from_secs(
+        days * 86_400 + hours * 3600 + minutes * 60 + seconds,
+    ))
+}
+
+/// Uniform in `[0, 1)`, good enough for jitter without pulling in a rng
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().hash_one(Instant::now());
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderValue;
+
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), HeaderValue::from_static(value)))
+            .collect()
+    }
+
+    #[test]
+    fn test_retry_after_headers() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_769_599_723);
+        let unavailable = StatusCode::SERVICE_UNAVAILABLE;
+        let limited = StatusCode::TOO_MANY_REQUESTS;
+
+        let cases = [
+            (unavailable, headers(&[("retry-after", "7")]), Some(7_000)),
+            (
+                unavailable,
+                headers(&[("retry-after-ms", "250.5"), ("retry-after", "7")]),
+                Some(250),
+            ),
+            (
+                unavailable,
+                headers(&[("retry-after-ms", "1e300"), ("retry-after", "7")]),
+                Some(7_000),
+            ),
+            (
+                unavailable,
+                headers(&[("retry-after", "Wed, 28 Jan 2026 11:28:50 GMT")]),
+                Some(7_000),
+            ),
+            (
+                unavailable,
+                headers(&[("retry-after", "Wed, 28 Jan 2026 11:28:20 GMT")]),
+                Some(0),
+            ),
+            (
+                unavailable,
+                headers(&[("x-ratelimit-reset", "1769599728000")]),
+                None,
+            ),
+            (
+                limited,
+                headers(&[("x-ratelimit-reset", "1769599728000")]),
+                Some(5_000),
+            ),
+            (
+                limited,
+                headers(&[
+                    ("x-ratelimit-reset-requests", "1s"),
+                    ("x-ratelimit-reset-tokens", "6m0s"),
+                ]),
+                Some(360_000),
+            ),
+            (
+                limited,
+                headers(&[("x-ratelimit-reset-requests", "1m2.5s")]),
+                Some(62_500),
+            ),
+            (limited, headers(&[("retry-after", "soon")]), None),
+            (
+                unavailable,
+                headers(&[("retry-after", "Mon, 01 Jan 99999999999999999 00:00:00 GMT")]),
+                None,
+            ),
+        ];
+
+        for (status, headers, expected) in cases {
+            assert_eq!(
+                retry_after(status, &headers, now).map(|delay| delay.as_millis() as u64),
+                expected,
+                "{:?}",
+                headers
+            );
+        }
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::default()
+            .with_initial_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(350))
+            .with_jitter(false);
+        let delays = (1..=4)
+            .map(|failures| policy.backoff(failures).as_millis())
+            .collect::<Vec<_>>();
+        assert_eq!(delays, [100, 200, 350, 350]);
+
+        let jittered = policy.clone().with_jitter(true).backoff(2);
+        assert!((Duration::from_millis(100)..=Duration::from_millis(200)).contains(&jittered));
+
+        // Out of attempts
+        assert_eq!(policy.next_delay(3, None, Duration::ZERO), None);
+        // The server's ask wins over a shorter backoff
+        assert_eq!(
+            policy.next_delay(1, Some(Duration::from_secs(2)), Duration::ZERO),
+            Some(Duration::from_secs(2))
+        );
+        // Waiting would pass the deadline
+        let policy = policy.with_deadline(Duration::from_secs(1));
+        assert_eq!(
+            policy.next_delay(1, Some(Duration::from_secs(2)), Duration::ZERO),
+            None
+        );
+        assert_eq!(
+            policy.next_delay(1, None, Duration::from_millis(500)),
+            Some(Duration::from_millis(100))
+        );
+    }
+}
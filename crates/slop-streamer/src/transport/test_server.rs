@@ -0,0 +1,78 @@
+use http::{HeaderValue, StatusCode, header::AUTHORIZATION};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+    task::JoinHandle,
+};
+use url::Url;
+
+use crate::transport::{HttpRequest, HttpTransport};
+
+/// Serve a single canned response, returning the URL to send to and the raw request that was received
+pub(crate) async fn serve_once(
+    status: u16,
+    content_type: &'static str,
+    body: String,
+) -> (Url, JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/api/v1", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+
+    let handle = tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut socket = BufReader::new(socket);
+
+        let mut request = String::new();
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            socket.read_line(&mut line).await.unwrap();
+            if let Some((name, value)) = line.split_once(':')
+                && name.eq_ignore_ascii_case("content-length")
+            {
+                content_length = value.trim().parse().unwrap();
+            }
+            request.push_str(&line);
+            if line == "\r\n" {
+                break;
+            }
+        }
+        let mut request_body = vec![0; content_length];
+        socket.read_exact(&mut request_body).await.unwrap();
+        request.push_str(std::str::from_utf8(&request_body).unwrap());
+
+        let response = format!(
+            "HTTP/1.1 {} Status\r\ncontent-type: {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        socket.shutdown().await.unwrap();
+        request
+    });
+
+    (url, handle)
+}
+
+/// Send a POST through `transport` and check both sides of the exchange, shared by the tests of every backend
+pub(crate) async fn round_trip<H: HttpTransport>(transport: H) {
+    let (url, server) = serve_once(418, "text/plain", "short and stout".to_string()).await;
+
+    let mut request = HttpRequest::post(url, "hello");
+    request
+        .headers
+        .insert(AUTHORIZATION, HeaderValue::from_static("Bearer key"));
+    let response = transport.send(request).await.unwrap();
+
+    assert_eq!(response.status, StatusCode::IM_A_TEAPOT);
+    assert_eq!(response.headers["content-type"], "text/plain");
+    assert_eq!(&response.collect().await.unwrap()[..], b"short and stout");
+
+    let sent = server.await.unwrap().to_ascii_lowercase();
+    assert!(sent.starts_with("post /api/v1 "));
+    assert!(sent.contains("authorization: bearer key\r\n"));
+    assert!(sent.ends_with("\r\n\r\nhello"));
+}